@@ -58,4 +58,72 @@ pub enum ErrorCode {
     OptionalAccountNotProvided,
     #[msg("You dont have the token that allows you to create products")]
     NotInWithelist,
+    #[msg("Product tokens of this marketplace can not be resold")]
+    NonTransferableProduct,
+    #[msg("You are providing an incorrect amount")]
+    IncorrectAmount,
+    #[msg("Creators are limited to 5, can not be repeated and each share must be positive and add up to 100")]
+    IncorrectCreators,
+    #[msg("Mint extensions are duplicated or incompatible")]
+    IncorrectExtensions,
+    #[msg("Error during the thaw account CPI")]
+    ThawAccountError,
+    #[msg("Your access to this marketplace has been revoked")]
+    AccessRevoked,
+    #[msg("Error during the freeze account CPI")]
+    FreezeAccountError,
+    #[msg("This access request has not expired yet")]
+    RequestNotExpired,
+    #[msg("The seller access to this marketplace has expired")]
+    AccessExpired,
+    #[msg("The NFT provided does not belong to the collection required by the marketplace")]
+    IncorrectCollection,
+    #[msg("The merkle proof provided is not valid")]
+    InvalidProof,
+    #[msg("This wallet is blocked in this marketplace")]
+    Blocked,
+    #[msg("The blocklist is full")]
+    BlocklistFull,
+    #[msg("Operations are paused")]
+    Paused,
+    #[msg("This payment mint is not accepted by the marketplace")]
+    PaymentMintNotAccepted,
+    #[msg("The decimals of the mint do not match")]
+    IncorrectDecimals,
+    #[msg("The campaign must end after it starts")]
+    IncorrectCampaignDates,
+    #[msg("The vesting cliff can not be longer than the vesting duration")]
+    IncorrectVestingSchedule,
+    #[msg("Loyalty points are not enabled in this marketplace")]
+    PointsNotEnabled,
+    #[msg("A wallet can not refer itself")]
+    SelfReferral,
+    #[msg("The product does not belong to this marketplace")]
+    IncorrectMarketplace,
+    #[msg("The royalty and the fee paid by the reseller can not exceed 100%")]
+    IncorrectRoyalty,
+    #[msg("The request expiry can not be negative")]
+    IncorrectRequestExpiry,
+    #[msg("There are less remaining accounts than the payment hook accounts")]
+    IncorrectHookAccounts,
+    #[msg("Collection gates need an NFT, a mint with a supply of 1 and no decimals")]
+    IncorrectGateMint,
+    #[msg("The NFT already gives access to another seller")]
+    GateClaimed,
+    #[msg("Creator signers must sign the transaction and be creators of the product")]
+    IncorrectCreatorSigners,
+    #[msg("The bounty vaults can not be withdrawn while there are open campaigns")]
+    OpenCampaigns,
+    #[msg("The access mint of the marketplace can not gate the buyers of a product")]
+    IncorrectBuyerGate,
+    #[msg("The account does not have the layout of a previous version")]
+    IncorrectAccountVersion,
+    #[msg("Only marketplaces that had rewards enabled migrate them to a campaign")]
+    IncorrectLegacyCampaign,
+    #[msg("The access mint does not give the marketplace the authority needed by the revoke mode")]
+    RevokeModeNotSupported,
+    #[msg("The access expiry has to be in the future")]
+    IncorrectAccessExpiry,
+    #[msg("The points discount can not be more than the marketplace fee of the payment mint")]
+    DiscountAboveFee,
 }
\ No newline at end of file
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+/// Emitted when a buyer burns product tokens, consumed by the seller's fulfillment system.
+#[event]
+pub struct RedemptionEvent {
+    pub redemption: Pubkey,
+    pub product: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+    pub reference: Option<[u8; 32]>,
+}
+
+/// Emitted when the marketplace authority rejects an access request.
+#[event]
+pub struct AccessDeniedEvent {
+    pub marketplace: Pubkey,
+    pub requester: Pubkey,
+    /// Code defined by the marketplace to explain the rejection.
+    pub reason: u16,
+}
+
+/// Emitted when a campaign can not pay the full rewards of a purchase because its budget or bounty vault ran out.
+#[event]
+pub struct RewardsExhaustedEvent {
+    pub campaign: Pubkey,
+    pub buyer: Pubkey,
+    pub requested: u64,
+    pub paid: u64,
+}
+
+/// Emitted when a bounty vault receives reward tokens.
+#[event]
+pub struct BountyFundedEvent {
+    pub marketplace: Pubkey,
+    pub mint: Pubkey,
+    pub funder: Pubkey,
+    pub amount: u64,
+    /// Balance of the bounty vault after the funding.
+    pub balance: u64,
+}
@@ -1,10 +1,10 @@
 use {
     crate::state::*,
     crate::error::ErrorCode,
+    crate::utils::{assert_access_expiry, grant_access},
     anchor_lang::prelude::*,
     anchor_spl::{
-        token_2022::mint_to,
-        token_interface::{Mint, MintTo, TokenInterface, TokenAccount},
+        token_interface::{Mint, TokenInterface, TokenAccount},
         token_2022::ID as TokenProgram2022,
         associated_token::AssociatedToken
     }
@@ -51,13 +51,25 @@ pub struct AcceptAccess<'info> {
     )]    
     pub access_mint: Box<InterfaceAccount<'info, Mint>>,
     #[account(
-        init,
+        init_if_needed,
         payer = signer,
         associated_token::mint = access_mint,
         associated_token::authority = receiver,
         associated_token::token_program = token_program
     )]
     pub access_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = SELLER_ACCESS_SIZE,
+        seeds = [
+            b"seller_access".as_ref(),
+            receiver.key().as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub seller_access: Box<Account<'info, SellerAccess>>,
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
     #[account(address = TokenProgram2022 @ ErrorCode::IncorrectTokenProgram)]
@@ -65,7 +77,7 @@ pub struct AcceptAccess<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
-pub fn handler<'info>(ctx: Context<AcceptAccess>) -> Result<()> {
+pub fn handler<'info>(ctx: Context<AcceptAccess>, expires_at: Option<i64>) -> Result<()> {
     let signer_key = ctx.accounts.signer.key();
     let marketplace_seeds = &[
         b"marketplace".as_ref(),
@@ -73,18 +85,24 @@ pub fn handler<'info>(ctx: Context<AcceptAccess>) -> Result<()> {
         &[ctx.accounts.marketplace.bumps.bump],
     ];
 
-    mint_to(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            MintTo {
-                mint: ctx.accounts.access_mint.to_account_info(),
-                to: ctx.accounts.access_vault.to_account_info(),
-                authority: ctx.accounts.marketplace.to_account_info(),
-            },
-            &[&marketplace_seeds[..]],
-        ),
-        1
-    ).map_err(|_| ErrorCode::MintToError)?;
+    grant_access(
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.access_mint.to_account_info(),
+        &ctx.accounts.access_vault,
+        ctx.accounts.access_vault.to_account_info(),
+        ctx.accounts.marketplace.to_account_info(),
+        &marketplace_seeds[..],
+    )?;
+
+    (*ctx.accounts.seller_access).authority = ctx.accounts.receiver.key();
+    (*ctx.accounts.seller_access).marketplace = ctx.accounts.marketplace.key();
+    // without a new expiry the current one is kept, new records never expire
+    assert_access_expiry(expires_at, Clock::get()?.unix_timestamp)?;
+    if let Some(expires_at) = expires_at {
+        (*ctx.accounts.seller_access).expires_at = expires_at;
+    }
+    (*ctx.accounts.seller_access).bump = ctx.bumps.seller_access;
+    (*ctx.accounts.seller_access).revoked = false;
     
     Ok(())
 }
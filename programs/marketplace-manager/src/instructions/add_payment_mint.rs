@@ -0,0 +1,60 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::Mint,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct AddPaymentMintParams {
+    pub decimals: u8,
+    pub fee_override: Option<u16>,
+}
+
+#[derive(Accounts)]
+pub struct AddPaymentMint<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [
+            b"marketplace".as_ref(),
+            signer.key().as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+        constraint = signer.key() == marketplace.authority
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = PAYMENT_MINT_CONFIG_SIZE,
+        seeds = [
+            b"payment_mint".as_ref(),
+            marketplace.key().as_ref(),
+            payment_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub payment_mint_config: Box<Account<'info, PaymentMintConfig>>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'info>(ctx: Context<AddPaymentMint>, params: AddPaymentMintParams) -> Result<()> {
+    if params.decimals != ctx.accounts.payment_mint.decimals {
+        return Err(ErrorCode::IncorrectDecimals.into());
+    }
+
+    if params.fee_override.unwrap_or_default() > 10000 {
+        return Err(ErrorCode::IncorrectFee.into());
+    }
+
+    (*ctx.accounts.payment_mint_config).marketplace = ctx.accounts.marketplace.key();
+    (*ctx.accounts.payment_mint_config).mint = ctx.accounts.payment_mint.key();
+    (*ctx.accounts.payment_mint_config).decimals = params.decimals;
+    (*ctx.accounts.payment_mint_config).fee_override = params.fee_override;
+    (*ctx.accounts.payment_mint_config).bump = ctx.bumps.payment_mint_config;
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct BlockWallet<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub wallet: SystemAccount<'info>,
+    #[account(
+        seeds = [
+            b"marketplace".as_ref(),
+            marketplace.authority.as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    #[account(
+        seeds = [
+            b"blocklist".as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump = blocklist.bump,
+        constraint = signer.key() == marketplace.authority || signer.key() == blocklist.delegate
+            @ErrorCode::IncorrectAuthority,
+    )]
+    pub blocklist: Box<Account<'info, Blocklist>>,
+    #[account(
+        init,
+        payer = signer,
+        space = BLOCKED_WALLET_SIZE,
+        seeds = [
+            b"blocked_wallet".as_ref(),
+            marketplace.key().as_ref(),
+            wallet.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub blocked_wallet: Box<Account<'info, BlockedWallet>>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'info>(ctx: Context<BlockWallet>) -> Result<()> {
+    (*ctx.accounts.blocked_wallet).wallet = ctx.accounts.wallet.key();
+    (*ctx.accounts.blocked_wallet).marketplace = ctx.accounts.marketplace.key();
+    (*ctx.accounts.blocked_wallet).bump = ctx.bumps.blocked_wallet;
+
+    Ok(())
+}
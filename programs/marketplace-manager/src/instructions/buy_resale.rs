@@ -0,0 +1,298 @@
+use {
+    crate::{
+        utils::*,
+        state::*,
+        error::ErrorCode,
+    },
+    anchor_lang::{
+        prelude::*,
+        system_program::System,
+    },
+    anchor_spl::{
+        token_interface::{
+            Mint,
+            TokenInterface,
+            TokenAccount,
+            CloseAccount,
+            close_account,
+        },
+        token_2022::ID as TokenProgram2022,
+    },
+    spl_token::native_mint::ID as NativeMint
+};
+
+#[derive(Accounts)]
+pub struct BuyResale<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    /// required with SOL payments and when the purchase sells the last listed tokens
+    #[account(
+        mut,
+        constraint = reseller.key() == listing.authority
+            @ ErrorCode::IncorrectAuthority
+    )]
+    pub reseller: Option<SystemAccount<'info>>,
+    #[account(
+        mut,
+        constraint = seller.key() == product.authority
+            @ ErrorCode::IncorrectAuthority
+    )]
+    pub seller: Option<SystemAccount<'info>>,
+    #[account(
+        mut,
+        constraint = marketplace_auth.key() == marketplace.authority
+            @ ErrorCode::IncorrectAuthority
+    )]
+    pub marketplace_auth: Option<SystemAccount<'info>>,
+    #[account(
+        seeds = [
+            b"marketplace".as_ref(),
+            marketplace.authority.as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    /// CHECK: validated with seeds, it is only deserialized if the guardian has been initialized
+    #[account(
+        seeds = [b"guardian".as_ref()],
+        bump,
+    )]
+    pub guardian: UncheckedAccount<'info>,
+    /// CHECK: validated with seeds, the buyer is blocked if it exists
+    #[account(
+        seeds = [
+            b"blocked_wallet".as_ref(),
+            marketplace.key().as_ref(),
+            signer.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub blocked_buyer: UncheckedAccount<'info>,
+    /// CHECK: validated with seeds, the reseller is blocked if it exists
+    #[account(
+        seeds = [
+            b"blocked_wallet".as_ref(),
+            marketplace.key().as_ref(),
+            listing.authority.as_ref(),
+        ],
+        bump,
+    )]
+    pub blocked_reseller: UncheckedAccount<'info>,
+    #[account(
+        seeds = [
+            b"product".as_ref(),
+            product.id.as_ref(),
+        ],
+        bump = product.bumps.bump,
+        constraint = product.marketplace == marketplace.key()
+            @ ErrorCode::IncorrectMarketplace,
+    )]
+    pub product: Box<Account<'info, Product>>,
+    #[account(
+        mut,
+        seeds = [
+            b"listing".as_ref(),
+            listing.authority.as_ref(),
+            product.key().as_ref(),
+        ],
+        bump = listing.bumps.bump,
+    )]
+    pub listing: Box<Account<'info, Listing>>,
+    #[account(
+        mut,
+        seeds = [
+            b"listing_vault".as_ref(),
+            listing.key().as_ref(),
+        ],
+        bump = listing.bumps.vault_bump,
+    )]
+    pub listing_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        constraint = product_mint.key() == product.product_mint
+            @ ErrorCode::IncorrectMint,
+    )]
+    pub product_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        constraint = buyer_token_vault.owner == signer.key()
+            @ ErrorCode::IncorrectAuthority,
+        constraint = buyer_token_vault.mint == product.product_mint
+            @ ErrorCode::IncorrectATA,
+    )]
+    pub buyer_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        constraint = payment_mint.key() == listing.payment_mint
+            @ ErrorCode::IncorrectMint,
+    )]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: validated with seeds, it is only deserialized if the payment mint has been added
+    #[account(
+        seeds = [
+            b"payment_mint".as_ref(),
+            marketplace.key().as_ref(),
+            payment_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub payment_mint_config: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = buyer_transfer_vault.owner == signer.key()
+            @ ErrorCode::IncorrectAuthority,
+        constraint = buyer_transfer_vault.mint == listing.payment_mint
+            @ ErrorCode::IncorrectATA,
+    )]
+    pub buyer_transfer_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        constraint = reseller_transfer_vault.owner == listing.authority
+            @ ErrorCode::IncorrectAuthority,
+        constraint = reseller_transfer_vault.mint == listing.payment_mint
+            @ ErrorCode::IncorrectATA,
+    )]
+    pub reseller_transfer_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// ATA that receives the royalties
+    #[account(
+        mut,
+        constraint = seller_transfer_vault.owner == product.authority
+            @ ErrorCode::IncorrectAuthority,
+        constraint = seller_transfer_vault.mint == listing.payment_mint
+            @ ErrorCode::IncorrectATA,
+    )]
+    pub seller_transfer_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// ATA that receives fees
+    #[account(
+        mut,
+        constraint = marketplace_transfer_vault.owner == marketplace.authority
+            @ ErrorCode::IncorrectAuthority,
+        constraint = marketplace_transfer_vault.mint == listing.payment_mint
+            @ ErrorCode::IncorrectATA,
+    )]
+    pub marketplace_transfer_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(address = TokenProgram2022 @ ErrorCode::IncorrectTokenProgram)]
+    pub token_program_2022: Interface<'info, TokenInterface>,
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, BuyResale<'info>>, amount: u64, payment_hook_accounts: u8) -> Result<()> {
+    assert_not_paused(&ctx.accounts.marketplace, &ctx.accounts.guardian)?;
+    assert_not_blocked(
+        &ctx.accounts.marketplace.permission_config,
+        &ctx.accounts.blocked_buyer,
+    )?;
+    assert_not_blocked(
+        &ctx.accounts.marketplace.permission_config,
+        &ctx.accounts.blocked_reseller,
+    )?;
+    // the payment transfers and the transfer of the product tokens get the hook accounts of their own mint
+    let (payment_hook_accounts, product_hook_accounts) = split_hook_accounts(ctx.remaining_accounts, payment_hook_accounts)?;
+
+    if amount == 0 || amount > ctx.accounts.listing.amount {
+        return Err(ErrorCode::IncorrectAmount.into());
+    }
+
+    let total_amount = ctx.accounts.listing.unit_price
+        .checked_mul(amount).ok_or(ErrorCode::NumericalOverflow)?;
+    let marketplace = &ctx.accounts.marketplace;
+    let payment_mint_config = load_payment_mint_config(&ctx.accounts.payment_mint_config)?;
+    assert_payment_mint(
+        &marketplace.fees_config,
+        &payment_mint_config,
+        ctx.accounts.payment_mint.decimals,
+    )?;
+    let fees_config = get_fees_config(&marketplace.fees_config, &payment_mint_config);
+
+    // payment, royalties and fees
+    if cmp_pubkeys(&ctx.accounts.payment_mint.key(), &NativeMint) {
+        let marketplace_auth = ctx.accounts.marketplace_auth.as_ref()
+            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+        let seller = ctx.accounts.seller.as_ref()
+            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+        let reseller = ctx.accounts.reseller.as_ref()
+            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+
+        handle_resale_sol(
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.signer.to_account_info(),
+            marketplace_auth.to_account_info(),
+            seller.to_account_info(),
+            reseller.to_account_info(),
+            fees_config.clone(),
+            ctx.accounts.product.seller_config.royalty,
+            ctx.accounts.listing.payment_mint,
+            total_amount,
+        )?;
+    } else {
+        let marketplace_transfer_vault = ctx.accounts.marketplace_transfer_vault.as_ref()
+            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+        let seller_transfer_vault = ctx.accounts.seller_transfer_vault.as_ref()
+            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+        let reseller_transfer_vault = ctx.accounts.reseller_transfer_vault.as_ref()
+            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+        let buyer_transfer_vault = ctx.accounts.buyer_transfer_vault.as_ref()
+            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+
+        handle_resale_spl(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.signer.to_account_info(),
+            marketplace_transfer_vault.to_account_info(),
+            seller_transfer_vault.to_account_info(),
+            reseller_transfer_vault.to_account_info(),
+            buyer_transfer_vault.to_account_info(),
+            ctx.accounts.payment_mint.to_account_info(),
+            payment_hook_accounts,
+            fees_config.clone(),
+            ctx.accounts.product.seller_config.royalty,
+            total_amount,
+        )?;
+    }
+
+    let product_key = ctx.accounts.product.key();
+    let reseller_key = ctx.accounts.listing.authority;
+    let listing_seeds = &[
+        b"listing".as_ref(),
+        reseller_key.as_ref(),
+        product_key.as_ref(),
+        &[ctx.accounts.listing.bumps.bump],
+    ];
+
+    transfer_checked_with_hook(
+        ctx.accounts.token_program_2022.to_account_info(),
+        ctx.accounts.listing_vault.to_account_info(),
+        ctx.accounts.product_mint.to_account_info(),
+        ctx.accounts.buyer_token_vault.to_account_info(),
+        ctx.accounts.listing.to_account_info(),
+        product_hook_accounts,
+        amount,
+        &[&listing_seeds[..]],
+    )?;
+
+    (*ctx.accounts.listing).amount = ctx.accounts.listing.amount
+        .checked_sub(amount).ok_or(ErrorCode::NumericalOverflow)?;
+
+    // the listing is closed once every token is sold, the reseller gets the rent back
+    if ctx.accounts.listing.amount == 0 {
+        let reseller = ctx.accounts.reseller.as_ref()
+            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+
+        // tokens sent directly to the vault keep the listing open, the reseller recovers them with cancel_resale
+        ctx.accounts.listing_vault.reload()?;
+        if ctx.accounts.listing_vault.amount == 0 {
+            close_account(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program_2022.to_account_info(),
+                    CloseAccount {
+                        account: ctx.accounts.listing_vault.to_account_info(),
+                        destination: reseller.to_account_info(),
+                        authority: ctx.accounts.listing.to_account_info(),
+                    },
+                    &[&listing_seeds[..]],
+                ),
+            ).map_err(|_| ErrorCode::CloseAccountError)?;
+            ctx.accounts.listing.close(reseller.to_account_info())?;
+        }
+    }
+
+    Ok(())
+}
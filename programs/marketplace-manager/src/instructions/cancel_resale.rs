@@ -0,0 +1,105 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    crate::utils::transfer_checked_with_hook,
+    anchor_lang::prelude::*,
+    anchor_spl::{
+        token_interface::{
+            Mint,
+            TokenAccount,
+            TokenInterface,
+            CloseAccount,
+            close_account,
+        },
+        token_2022::ID as TokenProgram2022,
+    },
+};
+
+#[derive(Accounts)]
+pub struct CancelResale<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [
+            b"product".as_ref(),
+            product.id.as_ref(),
+        ],
+        bump = product.bumps.bump,
+    )]
+    pub product: Box<Account<'info, Product>>,
+    #[account(
+        constraint = product_mint.key() == product.product_mint
+            @ ErrorCode::IncorrectMint,
+    )]
+    pub product_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [
+            b"listing".as_ref(),
+            signer.key().as_ref(),
+            product.key().as_ref(),
+        ],
+        bump = listing.bumps.bump,
+        constraint = signer.key() == listing.authority
+            @ ErrorCode::IncorrectAuthority,
+        close = signer,
+    )]
+    pub listing: Box<Account<'info, Listing>>,
+    #[account(
+        mut,
+        seeds = [
+            b"listing_vault".as_ref(),
+            listing.key().as_ref(),
+        ],
+        bump = listing.bumps.vault_bump,
+    )]
+    pub listing_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = reseller_token_vault.owner == signer.key()
+            @ ErrorCode::IncorrectAuthority,
+        constraint = reseller_token_vault.mint == product.product_mint
+            @ ErrorCode::IncorrectATA,
+    )]
+    pub reseller_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(address = TokenProgram2022 @ ErrorCode::IncorrectTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CancelResale<'info>>) -> Result<()> {
+    let signer_key = ctx.accounts.signer.key();
+    let product_key = ctx.accounts.product.key();
+    let listing_seeds = &[
+        b"listing".as_ref(),
+        signer_key.as_ref(),
+        product_key.as_ref(),
+        &[ctx.accounts.listing.bumps.bump],
+    ];
+
+    if ctx.accounts.listing_vault.amount > 0 {
+        transfer_checked_with_hook(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.listing_vault.to_account_info(),
+            ctx.accounts.product_mint.to_account_info(),
+            ctx.accounts.reseller_token_vault.to_account_info(),
+            ctx.accounts.listing.to_account_info(),
+            ctx.remaining_accounts,
+            ctx.accounts.listing_vault.amount,
+            &[&listing_seeds[..]],
+        )?;
+    }
+
+    close_account(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.listing_vault.to_account_info(),
+                destination: ctx.accounts.signer.to_account_info(),
+                authority: ctx.accounts.listing.to_account_info(),
+            },
+            &[&listing_seeds[..]],
+        ),
+    ).map_err(|_| ErrorCode::CloseAccountError)?;
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct CloseCampaign<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"marketplace".as_ref(),
+            signer.key().as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+        constraint = signer.key() == marketplace.authority
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    #[account(
+        mut,
+        close = signer,
+        seeds = [
+            b"campaign".as_ref(),
+            marketplace.key().as_ref(),
+            campaign.id.as_ref(),
+        ],
+        bump = campaign.bump,
+    )]
+    pub campaign: Box<Account<'info, RewardCampaign>>,
+}
+
+pub fn handler<'info>(ctx: Context<CloseCampaign>) -> Result<()> {
+    // the campaign stops rewarding purchases right away, rewards already credited keep their withdrawal time
+    (*ctx.accounts.marketplace).rewards_config.open_campaigns = ctx.accounts.marketplace.rewards_config.open_campaigns
+        .checked_sub(1).ok_or(ErrorCode::NumericalOverflow)?;
+
+    Ok(())
+}
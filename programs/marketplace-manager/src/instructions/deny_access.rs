@@ -0,0 +1,64 @@
+use {
+    crate::state::*,
+    crate::events::AccessDeniedEvent,
+    crate::error::ErrorCode,
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct DenyAccess<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub receiver: SystemAccount<'info>,
+    #[account(
+        seeds = [
+            b"marketplace".as_ref(),
+            signer.key().as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+        constraint = signer.key() == marketplace.authority
+            @ErrorCode::IncorrectAuthority,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    #[account(
+        mut,
+        seeds = [
+            b"request".as_ref(),
+            receiver.key().as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump = request.bump,
+        close = receiver,
+    )]
+    pub request: Account<'info, Access>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = ACCESS_DENIAL_SIZE,
+        seeds = [
+            b"access_denial".as_ref(),
+            receiver.key().as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub access_denial: Account<'info, AccessDenial>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'info>(ctx: Context<DenyAccess>, reason: u16) -> Result<()> {
+    (*ctx.accounts.access_denial).authority = ctx.accounts.receiver.key();
+    (*ctx.accounts.access_denial).marketplace = ctx.accounts.marketplace.key();
+    (*ctx.accounts.access_denial).reason = reason;
+    (*ctx.accounts.access_denial).denied_at = Clock::get()?.unix_timestamp;
+    (*ctx.accounts.access_denial).bump = ctx.bumps.access_denial;
+
+    emit!(AccessDeniedEvent {
+        marketplace: ctx.accounts.marketplace.key(),
+        requester: ctx.accounts.receiver.key(),
+        reason,
+    });
+
+    Ok(())
+}
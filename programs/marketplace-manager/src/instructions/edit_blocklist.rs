@@ -0,0 +1,35 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct EditBlocklist<'info> {
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [
+            b"marketplace".as_ref(),
+            signer.key().as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+        constraint = signer.key() == marketplace.authority
+            @ErrorCode::IncorrectAuthority,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    #[account(
+        mut,
+        seeds = [
+            b"blocklist".as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump = blocklist.bump,
+    )]
+    pub blocklist: Box<Account<'info, Blocklist>>,
+}
+
+pub fn handler<'info>(ctx: Context<EditBlocklist>, delegate: Pubkey) -> Result<()> {
+    (*ctx.accounts.blocklist).delegate = delegate;
+
+    Ok(())
+}
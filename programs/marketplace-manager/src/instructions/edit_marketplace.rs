@@ -9,12 +9,17 @@ use {
 pub struct EditMarketplaceParams {
     pub fee: u16,
     pub fee_reduction: u16,
-    pub seller_reward: u16,
-    pub buyer_reward: u16,
     pub transferable: bool,
     pub permissionless: bool,
-    pub rewards_enabled: bool,
+    pub request_expiry: i64,
+    pub access_gate: AccessGate,
     pub fee_payer: PaymentFeePayer,
+    pub restricted_payment_mints: bool,
+    pub vesting_cliff: i64,
+    pub vesting_duration: i64,
+    pub instant_cashback: bool,
+    pub referral_reward: u16,
+    pub referral_duration: i64,
 }
 
 #[derive(Accounts)]
@@ -32,8 +37,6 @@ pub struct EditMarketplace<'info> {
             @ ErrorCode::IncorrectAuthority,
     )]
     pub marketplace: Box<Account<'info, Marketplace>>,
-    /// CHECK: no need to validate, marketplace auth is the unique wallet who can call this instruction
-    pub reward_mint: UncheckedAccount<'info>,
     pub discount_mint: Box<InterfaceAccount<'info, Mint>>,
 }
 
@@ -41,15 +44,26 @@ pub fn handler<'info>(
     ctx: Context<EditMarketplace>, 
     params: EditMarketplaceParams,
 ) -> Result<()> {
-    if params.fee_reduction > 10000 || params.fee > 10000 || params.seller_reward > 10000 || params.buyer_reward > 10000 {
+    if params.fee_reduction > 10000 || params.fee > 10000 || params.referral_reward > 10000 {
         return Err(ErrorCode::IncorrectFee.into());
     }
 
+    if params.vesting_cliff < 0 || params.vesting_cliff > params.vesting_duration {
+        return Err(ErrorCode::IncorrectVestingSchedule.into());
+    }
+
+    if params.request_expiry < 0 {
+        return Err(ErrorCode::IncorrectRequestExpiry.into());
+    }
+
     (*ctx.accounts.marketplace).token_config = TokenConfig {
         transferable: params.transferable,
     };
     (*ctx.accounts.marketplace).permission_config = PermissionConfig {
         permissionless: params.permissionless,
+        request_expiry: params.request_expiry,
+        access_gate: params.access_gate,
+        blocklist_enabled: ctx.accounts.marketplace.permission_config.blocklist_enabled,
         access_mint: ctx.accounts.marketplace.permission_config.access_mint,
     };
     (*ctx.accounts.marketplace).fees_config = FeesConfig {
@@ -57,13 +71,13 @@ pub fn handler<'info>(
         fee: params.fee,
         fee_reduction: params.fee_reduction,
         fee_payer: params.fee_payer,
+        restricted_payment_mints: params.restricted_payment_mints,
     };
-    (*ctx.accounts.marketplace).rewards_config = RewardsConfig {
-        reward_mint: ctx.accounts.reward_mint.key(),
-        seller_reward: params.seller_reward,
-        buyer_reward: params.buyer_reward,
-        rewards_enabled: params.rewards_enabled,
-    };
+    (*ctx.accounts.marketplace).rewards_config.vesting_cliff = params.vesting_cliff;
+    (*ctx.accounts.marketplace).rewards_config.vesting_duration = params.vesting_duration;
+    (*ctx.accounts.marketplace).rewards_config.instant_cashback = params.instant_cashback;
+    (*ctx.accounts.marketplace).rewards_config.referral_reward = params.referral_reward;
+    (*ctx.accounts.marketplace).rewards_config.referral_duration = params.referral_duration;
     
     Ok(())
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use {
     crate::state::*,
     crate::error::ErrorCode,
+    crate::utils::{assert_buyer_gate, assert_payment_mint, load_payment_mint_config},
     anchor_lang::prelude::*,
     anchor_spl::token_interface::Mint
 };
@@ -31,13 +32,33 @@ pub struct EditProduct<'info> {
     pub marketplace: Box<Account<'info, Marketplace>>,
     /// CHECK: no need to validate, seller is the unique wallet who can call this instruction
     pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: validated with seeds, it is only deserialized if the payment mint has been added
+    #[account(
+        seeds = [
+            b"payment_mint".as_ref(),
+            marketplace.key().as_ref(),
+            payment_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub payment_mint_config: UncheckedAccount<'info>,
 }
 
-pub fn handler<'info>(ctx: Context<EditProduct>, product_price: u64) -> Result<()> {
+pub fn handler<'info>(ctx: Context<EditProduct>, product_price: u64, buyer_gate: Option<AccessGate>) -> Result<()> {
+    assert_buyer_gate(&buyer_gate)?;
+
+    assert_payment_mint(
+        &ctx.accounts.marketplace.fees_config,
+        &load_payment_mint_config(&ctx.accounts.payment_mint_config)?,
+        ctx.accounts.payment_mint.decimals,
+    )?;
+
     (*ctx.accounts.product).seller_config = SellerConfig {
         payment_mint: ctx.accounts.payment_mint.key(),
         product_price,
+        royalty: ctx.accounts.product.seller_config.royalty,
     };
+    (*ctx.accounts.product).buyer_gate = buyer_gate;
     
     Ok(())
 }
\ No newline at end of file
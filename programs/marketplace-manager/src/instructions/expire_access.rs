@@ -0,0 +1,43 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct ExpireAccess<'info> {
+    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub receiver: SystemAccount<'info>,
+    #[account(
+        seeds = [
+            b"marketplace".as_ref(),
+            marketplace.authority.as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    #[account(
+        mut,
+        seeds = [
+            b"request".as_ref(),
+            receiver.key().as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump = request.bump,
+        close = receiver,
+    )]
+    pub request: Account<'info, Access>,
+}
+
+pub fn handler<'info>(ctx: Context<ExpireAccess>) -> Result<()> {
+    let request_expiry = ctx.accounts.marketplace.permission_config.request_expiry;
+    let expires_at = ctx.accounts.request.created_at
+        .checked_add(request_expiry).ok_or(ErrorCode::NumericalOverflow)?;
+
+    if request_expiry == 0 || Clock::get()?.unix_timestamp < expires_at {
+        return Err(ErrorCode::RequestNotExpired.into());
+    }
+
+    Ok(())
+}
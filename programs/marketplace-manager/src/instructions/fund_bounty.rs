@@ -0,0 +1,75 @@
+use {
+    crate::{
+        state::*,
+        events::BountyFundedEvent,
+        error::ErrorCode,
+        utils::{assert_not_paused, transfer_checked_with_hook},
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::{Mint, TokenInterface, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct FundBounty<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [
+            b"marketplace".as_ref(),
+            marketplace.authority.as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    /// CHECK: validated with seeds, it is only deserialized if the guardian has been initialized
+    #[account(
+        seeds = [b"guardian".as_ref()],
+        bump,
+    )]
+    pub guardian: UncheckedAccount<'info>,
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        constraint = funder_vault.owner == signer.key()
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub funder_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [
+            b"bounty_vault".as_ref(),
+            marketplace.key().as_ref(),
+            reward_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub bounty_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, FundBounty<'info>>, amount: u64) -> Result<()> {
+    assert_not_paused(&ctx.accounts.marketplace, &ctx.accounts.guardian)?;
+
+    transfer_checked_with_hook(
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.funder_vault.to_account_info(),
+        ctx.accounts.reward_mint.to_account_info(),
+        ctx.accounts.bounty_vault.to_account_info(),
+        ctx.accounts.signer.to_account_info(),
+        ctx.remaining_accounts,
+        amount,
+        &[],
+    )?;
+
+    ctx.accounts.bounty_vault.reload()?;
+
+    emit!(BountyFundedEvent {
+        marketplace: ctx.accounts.marketplace.key(),
+        mint: ctx.accounts.reward_mint.key(),
+        funder: ctx.accounts.signer.key(),
+        amount,
+        balance: ctx.accounts.bounty_vault.amount,
+    });
+
+    Ok(())
+}
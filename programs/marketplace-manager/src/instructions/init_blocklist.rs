@@ -0,0 +1,43 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct InitBlocklist<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"marketplace".as_ref(),
+            signer.key().as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+        constraint = signer.key() == marketplace.authority
+            @ErrorCode::IncorrectAuthority,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    #[account(
+        init,
+        payer = signer,
+        space = BLOCKLIST_SIZE,
+        seeds = [
+            b"blocklist".as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub blocklist: Box<Account<'info, Blocklist>>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'info>(ctx: Context<InitBlocklist>, delegate: Pubkey) -> Result<()> {
+    (*ctx.accounts.blocklist).marketplace = ctx.accounts.marketplace.key();
+    (*ctx.accounts.blocklist).delegate = delegate;
+    (*ctx.accounts.blocklist).bump = ctx.bumps.blocklist;
+    (*ctx.accounts.marketplace).permission_config.blocklist_enabled = true;
+
+    Ok(())
+}
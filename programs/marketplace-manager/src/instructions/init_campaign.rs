@@ -0,0 +1,89 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::Mint,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct InitCampaignParams {
+    pub id: [u8; 16],
+    pub start: i64,
+    pub end: i64,
+    pub budget: u64,
+    pub max_reward: u64,
+    pub payout_rate: u64,
+    pub seller_reward: u16,
+    pub buyer_reward: u16,
+}
+
+#[derive(Accounts)]
+#[instruction(params: InitCampaignParams)]
+pub struct InitCampaign<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"marketplace".as_ref(),
+            signer.key().as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+        constraint = signer.key() == marketplace.authority
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    /// CHECK: can be the "null" PDA to reward any payment mint
+    pub reward_mint: UncheckedAccount<'info>,
+    /// the rewards are paid in the payment mint if it is not provided
+    pub payout_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(
+        init,
+        payer = signer,
+        space = REWARD_CAMPAIGN_SIZE,
+        seeds = [
+            b"campaign".as_ref(),
+            marketplace.key().as_ref(),
+            params.id.as_ref(),
+        ],
+        bump,
+    )]
+    pub campaign: Box<Account<'info, RewardCampaign>>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'info>(ctx: Context<InitCampaign>, params: InitCampaignParams) -> Result<()> {
+    if params.seller_reward > 10000 || params.buyer_reward > 10000 {
+        return Err(ErrorCode::IncorrectFee.into());
+    }
+
+    if params.end <= params.start {
+        return Err(ErrorCode::IncorrectCampaignDates.into());
+    }
+
+    let payout_mint = ctx.accounts.payout_mint.as_ref()
+        .map(|payout_mint| payout_mint.key())
+        .unwrap_or_default();
+    if payout_mint != Pubkey::default() && params.payout_rate == 0 {
+        return Err(ErrorCode::IncorrectAmount.into());
+    }
+
+    (*ctx.accounts.campaign).marketplace = ctx.accounts.marketplace.key();
+    (*ctx.accounts.campaign).id = params.id;
+    (*ctx.accounts.campaign).start = params.start;
+    (*ctx.accounts.campaign).end = params.end;
+    (*ctx.accounts.campaign).budget = params.budget;
+    (*ctx.accounts.campaign).spent = 0;
+    (*ctx.accounts.campaign).max_reward = params.max_reward;
+    (*ctx.accounts.campaign).reward_mint = ctx.accounts.reward_mint.key();
+    (*ctx.accounts.campaign).payout_mint = payout_mint;
+    (*ctx.accounts.campaign).payout_rate = params.payout_rate;
+    (*ctx.accounts.campaign).seller_reward = params.seller_reward;
+    (*ctx.accounts.campaign).buyer_reward = params.buyer_reward;
+    (*ctx.accounts.campaign).bump = ctx.bumps.campaign;
+
+    (*ctx.accounts.marketplace).rewards_config.open_campaigns = ctx.accounts.marketplace.rewards_config.open_campaigns
+        .checked_add(1).ok_or(ErrorCode::NumericalOverflow)?;
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    crate::program::MarketplaceManager,
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct InitGuardian<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        init,
+        payer = signer,
+        space = GUARDIAN_SIZE,
+        seeds = [b"guardian".as_ref()],
+        bump,
+    )]
+    pub guardian: Box<Account<'info, Guardian>>,
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key())
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub program: Program<'info, MarketplaceManager>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(signer.key())
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'info>(ctx: Context<InitGuardian>, authority: Pubkey) -> Result<()> {
+    (*ctx.accounts.guardian).authority = authority;
+    (*ctx.accounts.guardian).paused = false;
+    (*ctx.accounts.guardian).bump = ctx.bumps.guardian;
+
+    Ok(())
+}
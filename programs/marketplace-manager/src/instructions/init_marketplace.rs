@@ -3,8 +3,7 @@ use {
     crate::utils::assert_derivation,
     anchor_lang::prelude::*,
     crate::error::ErrorCode,
-    crate::utils::mint_builder,
-    spl_token_2022::extension::ExtensionType,
+    crate::utils::{mint_builder, MintExtension},
     anchor_spl::{
         token_interface::{
             Mint, 
@@ -20,13 +19,18 @@ use {
 pub struct InitMarketplaceParams {
     pub fee: u16,
     pub fee_reduction: u16,
-    pub seller_reward: u16,
-    pub buyer_reward: u16,
     pub transferable: bool,
     pub permissionless: bool,
-    pub rewards_enabled: bool,
+    pub request_expiry: i64,
+    pub access_gate: AccessGate,
     pub access_mint_bump: u8,
     pub fee_payer: PaymentFeePayer,
+    pub restricted_payment_mints: bool,
+    pub vesting_cliff: i64,
+    pub vesting_duration: i64,
+    pub instant_cashback: bool,
+    pub referral_reward: u16,
+    pub referral_duration: i64,
 }
 
 #[derive(Accounts)]
@@ -80,10 +84,18 @@ pub struct InitMarketplace<'info> {
 }
 
 pub fn handler<'info>(ctx: Context<InitMarketplace>, params: InitMarketplaceParams) -> Result<()> {
-    if params.fee_reduction > 10000 || params.fee > 10000 || params.seller_reward > 10000 || params.buyer_reward > 10000 {
+    if params.fee_reduction > 10000 || params.fee > 10000 || params.referral_reward > 10000 {
         return Err(ErrorCode::IncorrectFee.into());
     }
 
+    if params.vesting_cliff < 0 || params.vesting_cliff > params.vesting_duration {
+        return Err(ErrorCode::IncorrectVestingSchedule.into());
+    }
+
+    if params.request_expiry < 0 {
+        return Err(ErrorCode::IncorrectRequestExpiry.into());
+    }
+
     let signer_key = ctx.accounts.signer.key();
     let marketplace_key = ctx.accounts.marketplace.key();
     let mint_seeds: &[&[u8]] = &[
@@ -105,7 +117,12 @@ pub fn handler<'info>(ctx: Context<InitMarketplace>, params: InitMarketplacePara
     mint_builder(
         signer_mint_seeds,
         marketplace_seeds.to_vec(),
-        vec![ExtensionType::NonTransferable],
+        // the marketplace can burn or freeze the access token of a seller
+        vec![
+            MintExtension::NonTransferable,
+            MintExtension::PermanentDelegate { delegate: marketplace_key },
+        ],
+        Some(marketplace_key),
         ctx.accounts.system_program.to_account_info(),
         ctx.accounts.token_program_2022.to_account_info(),
         ctx.accounts.rent.to_account_info(),
@@ -121,6 +138,9 @@ pub fn handler<'info>(ctx: Context<InitMarketplace>, params: InitMarketplacePara
     };
     (*ctx.accounts.marketplace).permission_config = PermissionConfig {
         permissionless: params.permissionless,
+        request_expiry: params.request_expiry,
+        access_gate: params.access_gate,
+        blocklist_enabled: false,
         access_mint: ctx.accounts.access_mint.key(),
     };
     (*ctx.accounts.marketplace).fees_config = FeesConfig {
@@ -128,16 +148,24 @@ pub fn handler<'info>(ctx: Context<InitMarketplace>, params: InitMarketplacePara
         fee: params.fee,
         fee_reduction: params.fee_reduction,
         fee_payer: params.fee_payer,
+        restricted_payment_mints: params.restricted_payment_mints,
     };
     (*ctx.accounts.marketplace).rewards_config = RewardsConfig {
-        reward_mint: ctx.accounts.reward_mint.key(),
-        seller_reward: params.seller_reward,
-        buyer_reward: params.buyer_reward,
-        rewards_enabled: params.rewards_enabled,
+        open_campaigns: 0,
+        vesting_cliff: params.vesting_cliff,
+        vesting_duration: params.vesting_duration,
+        points_mint: Pubkey::default(),
+        points_discount: 0,
+        instant_cashback: params.instant_cashback,
+        referral_reward: params.referral_reward,
+        referral_duration: params.referral_duration,
     };
+    (*ctx.accounts.marketplace).paused = false;
     (*ctx.accounts.marketplace).bumps = MarketplaceBumps {
         bump: ctx.bumps.marketplace,
         access_mint_bump: params.access_mint_bump,
+        // derived once, purchases rebuild the address with this bump
+        null_bump: Pubkey::find_program_address(&[b"null".as_ref()], ctx.program_id).1,
     };
 
     Ok(())
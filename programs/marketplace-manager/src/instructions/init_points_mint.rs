@@ -0,0 +1,80 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    crate::utils::{mint_builder, MintExtension},
+    anchor_lang::prelude::*,
+    anchor_spl::{
+        token_interface::TokenInterface,
+        token_2022::ID as TokenProgram2022,
+    },
+};
+
+#[derive(Accounts)]
+pub struct InitPointsMint<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"marketplace".as_ref(),
+            signer.key().as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+        constraint = signer.key() == marketplace.authority
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    /// CHECK: this mint is init in the instruction logic
+    #[account(
+        mut,
+        seeds = [
+            b"points_mint".as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub points_mint: AccountInfo<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    #[account(address = TokenProgram2022 @ ErrorCode::IncorrectTokenProgram)]
+    pub token_program_2022: Interface<'info, TokenInterface>,
+}
+
+pub fn handler<'info>(ctx: Context<InitPointsMint>, points_discount: u16) -> Result<()> {
+    if points_discount > 10000 {
+        return Err(ErrorCode::IncorrectFee.into());
+    }
+
+    let signer_key = ctx.accounts.signer.key();
+    let marketplace_key = ctx.accounts.marketplace.key();
+    let mint_seeds: &[&[u8]] = &[
+        b"points_mint",
+        marketplace_key.as_ref(),
+        &[ctx.bumps.points_mint],
+    ];
+    let marketplace_seeds = &[
+        b"marketplace".as_ref(),
+        signer_key.as_ref(),
+        &[ctx.accounts.marketplace.bumps.bump],
+    ];
+
+    // points can not be sold, they are only earned buying and burned with spend_points
+    mint_builder(
+        mint_seeds.to_vec(),
+        marketplace_seeds.to_vec(),
+        vec![MintExtension::NonTransferable],
+        None,
+        ctx.accounts.system_program.to_account_info(),
+        ctx.accounts.token_program_2022.to_account_info(),
+        ctx.accounts.rent.to_account_info(),
+        ctx.accounts.points_mint.to_account_info(),
+        ctx.accounts.marketplace.to_account_info(),
+        ctx.accounts.signer.to_account_info(),
+        ctx.accounts.rent.clone(),
+    )?;
+
+    (*ctx.accounts.marketplace).rewards_config.points_mint = ctx.accounts.points_mint.key();
+    (*ctx.accounts.marketplace).rewards_config.points_discount = points_discount;
+
+    Ok(())
+}
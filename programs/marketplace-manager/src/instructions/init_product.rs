@@ -1,9 +1,8 @@
 use {
     crate::state::*,
     crate::error::ErrorCode,
-    crate::utils::{assert_derivation, mint_builder},
+    crate::utils::{assert_derivation, assert_access_gate, assert_not_blocked, claim_gate_mint, assert_not_paused, assert_payment_mint, assert_seller_access, load_payment_mint_config, mint_builder, MintExtension, assert_creators, assert_buyer_gate},
     anchor_lang::prelude::*,
-    spl_token_2022::extension::ExtensionType,
     anchor_lang::system_program::System,
     anchor_spl::{
         token_interface::{
@@ -12,6 +11,7 @@ use {
             TokenAccount
         },
         token_2022::ID as TokenProgram2022,
+        metadata::MetadataAccount,
     },
 };
 
@@ -19,7 +19,16 @@ use {
 pub struct InitProductParams {
     pub id: [u8; 16],
     pub product_price: u64,
-    pub product_mint_bump: u8
+    pub royalty: u16,
+    /// Wallets that share the royalties, defaults to the seller when empty.
+    pub creators: Vec<ProductCreator>,
+    /// Token-2022 extensions of the product mint, NonTransferable is added if the marketplace requires it.
+    pub extensions: Vec<MintExtension>,
+    pub product_mint_bump: u8,
+    /// Only needed when the marketplace uses an allowlist to gate sellers.
+    pub proof: Vec<[u8; 32]>,
+    /// Buyers need to satisfy this gate to buy the product.
+    pub buyer_gate: Option<AccessGate>,
 }
 
 #[derive(Accounts)]
@@ -36,6 +45,12 @@ pub struct InitProduct<'info> {
         bump = marketplace.bumps.bump,
     )]
     pub marketplace: Box<Account<'info, Marketplace>>,
+    /// CHECK: validated with seeds, it is only deserialized if the guardian has been initialized
+    #[account(
+        seeds = [b"guardian".as_ref()],
+        bump,
+    )]
+    pub guardian: UncheckedAccount<'info>,
     #[account(
         init,
         payer = signer,
@@ -58,6 +73,16 @@ pub struct InitProduct<'info> {
     )]    
     pub product_mint: AccountInfo<'info>,
     pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: validated with seeds, it is only deserialized if the payment mint has been added
+    #[account(
+        seeds = [
+            b"payment_mint".as_ref(),
+            marketplace.key().as_ref(),
+            payment_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub payment_mint_config: UncheckedAccount<'info>,
     #[account(
         mut,
         seeds = [
@@ -69,14 +94,40 @@ pub struct InitProduct<'info> {
             @ ErrorCode::IncorrectMint
     )]    
     pub access_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// mint validated in the ix logic, depends on the access gate of the marketplace
     #[account(
         mut,
-        constraint = access_vault.mint == marketplace.permission_config.access_mint
-            @ ErrorCode::IncorrectMint,
         constraint = access_vault.owner == signer.key()
             @ ErrorCode::IncorrectAuthority
     )]    
     pub access_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// required when the marketplace is gated by a collection, metadata of the NFT held in the access vault
+    pub gate_metadata: Option<Box<Account<'info, MetadataAccount>>>,
+    /// required when the marketplace is gated by a collection, mint of the NFT held in the access vault
+    pub gate_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// CHECK: validated in the ix logic, required when the marketplace is gated by a collection so each NFT gives access to one seller
+    #[account(mut)]
+    pub gate_claim: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated with seeds, sellers that got access before the access records existed do not have one
+    #[account(
+        seeds = [
+            b"seller_access".as_ref(),
+            signer.key().as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub seller_access: UncheckedAccount<'info>,
+    /// CHECK: validated with seeds, the seller is blocked if it exists
+    #[account(
+        seeds = [
+            b"blocked_wallet".as_ref(),
+            marketplace.key().as_ref(),
+            signer.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub blocked_seller: UncheckedAccount<'info>,
     pub rent: Sysvar<'info, Rent>,
     pub system_program: Program<'info, System>,
     #[account(address = TokenProgram2022 @ ErrorCode::IncorrectTokenProgram)]
@@ -84,12 +135,53 @@ pub struct InitProduct<'info> {
 }
 
 pub fn handler<'info>(ctx: Context<InitProduct>, params: InitProductParams) -> Result<()> {
+    if params.royalty > 10000 {
+        return Err(ErrorCode::IncorrectFee.into());
+    }
+
+    assert_creators(&params.creators)?;
+    assert_buyer_gate(&params.buyer_gate)?;
+
+    assert_not_paused(&ctx.accounts.marketplace, &ctx.accounts.guardian)?;
+
+    assert_payment_mint(
+        &ctx.accounts.marketplace.fees_config,
+        &load_payment_mint_config(&ctx.accounts.payment_mint_config)?,
+        ctx.accounts.payment_mint.decimals,
+    )?;
+
+    assert_not_blocked(
+        &ctx.accounts.marketplace.permission_config,
+        &ctx.accounts.blocked_seller,
+    )?;
+
     if !ctx.accounts.marketplace.permission_config.permissionless {
-        let access_vault = ctx.accounts.access_vault.as_ref()
-            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+        assert_access_gate(
+            &ctx.accounts.marketplace.permission_config.access_gate,
+            &ctx.accounts.marketplace.permission_config.access_mint,
+            &ctx.accounts.signer.key(),
+            ctx.accounts.access_vault.as_deref(),
+            ctx.accounts.gate_mint.as_deref(),
+            ctx.accounts.gate_metadata.as_deref(),
+            &params.proof,
+        )?;
+
+        if let AccessGate::Collection { .. } = ctx.accounts.marketplace.permission_config.access_gate {
+            let gate_mint = ctx.accounts.gate_mint.as_ref()
+                .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+            let gate_claim = ctx.accounts.gate_claim.as_ref()
+                .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+            claim_gate_mint(
+                &gate_claim.to_account_info(),
+                &ctx.accounts.marketplace.key(),
+                &gate_mint.key(),
+                &ctx.accounts.signer.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+            )?;
+        }
 
-        if access_vault.amount == 0 {
-            return Err(ErrorCode::NotInWithelist.into());
+        if ctx.accounts.marketplace.permission_config.access_gate == AccessGate::AccessMint {
+            assert_seller_access(&ctx.accounts.seller_access)?;
         }
     }
 
@@ -101,11 +193,22 @@ pub fn handler<'info>(ctx: Context<InitProduct>, params: InitProductParams) -> R
     (*ctx.accounts.product).seller_config = SellerConfig {
         payment_mint: ctx.accounts.payment_mint.key(),
         product_price: params.product_price,
+        royalty: params.royalty,
     };
+    (*ctx.accounts.product).creators = if params.creators.is_empty() {
+        Vec::from([ProductCreator {
+            address: ctx.accounts.signer.key(),
+            share: 100,
+        }])
+    } else {
+        params.creators
+    };
+    (*ctx.accounts.product).buyer_gate = params.buyer_gate;
     (*ctx.accounts.product).bumps = ProductBumps {
         bump: ctx.bumps.product,
         mint_bump: params.product_mint_bump,
     };
+    (*ctx.accounts.product).marketplace = ctx.accounts.marketplace.key();
 
     let mint_seeds: &[&[u8]] = &[
         b"product_mint",
@@ -123,16 +226,23 @@ pub fn handler<'info>(ctx: Context<InitProduct>, params: InitProductParams) -> R
         &[ctx.accounts.product.bumps.bump],
     ];
 
-    let extensions = if ctx.accounts.marketplace.token_config.transferable {
-        vec![]
+    let mut extensions = params.extensions;
+    if !ctx.accounts.marketplace.token_config.transferable && !extensions.contains(&MintExtension::NonTransferable) {
+        extensions.push(MintExtension::NonTransferable);
+    }
+
+    // the product pda thaws the buyer token account when a purchase is registered
+    let freeze_authority = if extensions.contains(&MintExtension::DefaultAccountState { frozen: true }) {
+        Some(product_key)
     } else {
-        vec![ExtensionType::NonTransferable]
+        None
     };
 
     mint_builder(
         signer_mint_seeds,
         product_seeds.to_vec(),
         extensions,
+        freeze_authority,
         ctx.accounts.system_program.to_account_info(),
         ctx.accounts.token_program.to_account_info(),
         ctx.accounts.rent.to_account_info(),
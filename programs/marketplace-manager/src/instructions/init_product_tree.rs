@@ -1,6 +1,6 @@
 use {
     crate::state::*,
-    crate::utils::assert_derivation,
+    crate::utils::{assert_derivation, assert_access_gate, assert_not_blocked, claim_gate_mint, assert_not_paused, assert_payment_mint, assert_seller_access, load_payment_mint_config, assert_creators, assert_buyer_gate},
     crate::error::ErrorCode,
     anchor_lang::prelude::*,
     anchor_lang::system_program::System,
@@ -12,7 +12,10 @@ use {
             CreateMasterEditionV3, 
             create_metadata_accounts_v3,
             CreateMetadataAccountsV3,
+            sign_metadata,
+            SignMetadata,
             mpl_token_metadata::types::{DataV2, Creator, CollectionDetails},
+            MetadataAccount,
             ID as TOKEN_METADATA_ID
         },
     },
@@ -29,6 +32,11 @@ pub struct InitProductTreeParams {
     pub name: String,
     pub metadata_url: String,
     pub fee_basis_points: u16,
+    pub creators: Vec<ProductCreator>,
+    /// Only needed when the marketplace uses an allowlist to gate sellers.
+    pub proof: Vec<[u8; 32]>,
+    /// Buyers need to satisfy this gate to buy the product.
+    pub buyer_gate: Option<AccessGate>,
 }
 
 #[derive(Accounts)]
@@ -45,6 +53,12 @@ pub struct InitProductTree<'info> {
         bump = marketplace.bumps.bump,
     )]
     pub marketplace: Box<Account<'info, Marketplace>>,
+    /// CHECK: validated with seeds, it is only deserialized if the guardian has been initialized
+    #[account(
+        seeds = [b"guardian".as_ref()],
+        bump,
+    )]
+    pub guardian: UncheckedAccount<'info>,
     #[account(
         init,
         payer = signer,
@@ -72,6 +86,16 @@ pub struct InitProductTree<'info> {
     )]
     pub product_mint: Box<InterfaceAccount<'info, Mint>>,
     pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: validated with seeds, it is only deserialized if the payment mint has been added
+    #[account(
+        seeds = [
+            b"payment_mint".as_ref(),
+            marketplace.key().as_ref(),
+            payment_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub payment_mint_config: UncheckedAccount<'info>,
     #[account(
         mut,
         seeds = [
@@ -91,14 +115,40 @@ pub struct InitProductTree<'info> {
         associated_token::token_program = token_program
     )]
     pub product_mint_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// mint validated in the ix logic, depends on the access gate of the marketplace
     #[account(
         mut,
-        constraint = access_vault.mint == marketplace.permission_config.access_mint
-            @ ErrorCode::IncorrectMint,
         constraint = access_vault.owner == signer.key()
             @ ErrorCode::IncorrectAuthority
     )]    
     pub access_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// required when the marketplace is gated by a collection, metadata of the NFT held in the access vault
+    pub gate_metadata: Option<Box<Account<'info, MetadataAccount>>>,
+    /// required when the marketplace is gated by a collection, mint of the NFT held in the access vault
+    pub gate_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// CHECK: validated in the ix logic, required when the marketplace is gated by a collection so each NFT gives access to one seller
+    #[account(mut)]
+    pub gate_claim: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated with seeds, sellers that got access before the access records existed do not have one
+    #[account(
+        seeds = [
+            b"seller_access".as_ref(),
+            signer.key().as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub seller_access: UncheckedAccount<'info>,
+    /// CHECK: validated with seeds, the seller is blocked if it exists
+    #[account(
+        seeds = [
+            b"blocked_wallet".as_ref(),
+            marketplace.key().as_ref(),
+            signer.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub blocked_seller: UncheckedAccount<'info>,
     /// CHECK: Handled by cpi
     #[account(
         mut,
@@ -152,12 +202,53 @@ pub struct InitProductTree<'info> {
 }
 
 pub fn handler<'info>(ctx: Context<InitProductTree>, params: InitProductTreeParams) -> Result<()> {
+    if params.fee_basis_points > 10000 {
+        return Err(ErrorCode::IncorrectFee.into());
+    }
+
+    assert_creators(&params.creators)?;
+    assert_buyer_gate(&params.buyer_gate)?;
+
+    assert_not_paused(&ctx.accounts.marketplace, &ctx.accounts.guardian)?;
+
+    assert_payment_mint(
+        &ctx.accounts.marketplace.fees_config,
+        &load_payment_mint_config(&ctx.accounts.payment_mint_config)?,
+        ctx.accounts.payment_mint.decimals,
+    )?;
+
+    assert_not_blocked(
+        &ctx.accounts.marketplace.permission_config,
+        &ctx.accounts.blocked_seller,
+    )?;
+
     if !ctx.accounts.marketplace.permission_config.permissionless {
-        let access_vault = ctx.accounts.access_vault.as_ref()
-            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+        assert_access_gate(
+            &ctx.accounts.marketplace.permission_config.access_gate,
+            &ctx.accounts.marketplace.permission_config.access_mint,
+            &ctx.accounts.signer.key(),
+            ctx.accounts.access_vault.as_deref(),
+            ctx.accounts.gate_mint.as_deref(),
+            ctx.accounts.gate_metadata.as_deref(),
+            &params.proof,
+        )?;
+
+        if let AccessGate::Collection { .. } = ctx.accounts.marketplace.permission_config.access_gate {
+            let gate_mint = ctx.accounts.gate_mint.as_ref()
+                .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+            let gate_claim = ctx.accounts.gate_claim.as_ref()
+                .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+            claim_gate_mint(
+                &gate_claim.to_account_info(),
+                &ctx.accounts.marketplace.key(),
+                &gate_mint.key(),
+                &ctx.accounts.signer.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+            )?;
+        }
 
-        if access_vault.amount == 0 {
-            return Err(ErrorCode::NotInWithelist.into());
+        if ctx.accounts.marketplace.permission_config.access_gate == AccessGate::AccessMint {
+            assert_seller_access(&ctx.accounts.seller_access)?;
         }
     }
 
@@ -170,11 +261,22 @@ pub fn handler<'info>(ctx: Context<InitProductTree>, params: InitProductTreePara
     (*ctx.accounts.product).seller_config = SellerConfig {
         payment_mint: ctx.accounts.payment_mint.key(),
         product_price: params.product_price,
+        royalty: params.fee_basis_points,
+    };
+    (*ctx.accounts.product).creators = if params.creators.is_empty() {
+        Vec::from([ProductCreator {
+            address: ctx.accounts.signer.key(),
+            share: 100,
+        }])
+    } else {
+        params.creators
     };
+    (*ctx.accounts.product).buyer_gate = params.buyer_gate;
     (*ctx.accounts.product).bumps = ProductBumps {
         bump: ctx.bumps.product,
         mint_bump: ctx.bumps.product_mint,
     };
+    (*ctx.accounts.product).marketplace = ctx.accounts.marketplace.key();
 
     let mint_seeds: &[&[u8]] = &[
         b"product_mint",
@@ -223,12 +325,14 @@ pub fn handler<'info>(ctx: Context<InitProductTree>, params: InitProductTreePara
             name: params.name.clone(),
             symbol: "BRICK".to_string(),
             uri: params.metadata_url,
-            seller_fee_basis_points: 0,
-            creators: Some(Vec::from([Creator {
-                address: ctx.accounts.product.authority,
-                verified: false,
-                share: 100,
-            }])),
+            seller_fee_basis_points: params.fee_basis_points,
+            // only the update authority (product pda) can be verified when creating the metadata,
+            // the seller signs the metadata afterwards
+            creators: Some(ctx.accounts.product.creators.iter().map(|creator| Creator {
+                address: creator.address,
+                verified: creator.address == product_key,
+                share: creator.share,
+            }).collect()),
             collection: None,
             uses: None,
         },
@@ -237,6 +341,20 @@ pub fn handler<'info>(ctx: Context<InitProductTree>, params: InitProductTreePara
         Some(CollectionDetails::V1 { size: 0 }),
     )?;
 
+    // the seller signs this instruction, so it is verified when it is one of the creators
+    let signer_key = ctx.accounts.signer.key();
+    if ctx.accounts.product.creators.iter().any(|creator| creator.address == signer_key) {
+        sign_metadata(
+            CpiContext::new(
+                ctx.accounts.token_metadata_program.clone(),
+                SignMetadata {
+                    creator: ctx.accounts.signer.to_account_info(),
+                    metadata: ctx.accounts.metadata.to_account_info(),
+                },
+            ),
+        )?;
+    }
+
     create_master_edition_v3(
         CpiContext::new_with_signer(
             ctx.accounts.token_metadata_program.clone(),
@@ -0,0 +1,86 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::{Mint, TokenInterface, TokenAccount},
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct InitPromotionParams {
+    pub id: [u8; 16],
+    pub start: i64,
+    pub end: i64,
+    pub buyer_reward: u16,
+}
+
+#[derive(Accounts)]
+#[instruction(params: InitPromotionParams)]
+pub struct InitPromotion<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [
+            b"product".as_ref(),
+            product.id.as_ref(),
+        ],
+        bump = product.bumps.bump,
+        constraint = signer.key() == product.authority
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub product: Box<Account<'info, Product>>,
+    #[account(
+        constraint = payment_mint.key() == product.seller_config.payment_mint
+            @ ErrorCode::IncorrectMint,
+    )]
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = signer,
+        space = PROMOTION_SIZE,
+        seeds = [
+            b"promotion".as_ref(),
+            product.key().as_ref(),
+            params.id.as_ref(),
+        ],
+        bump,
+    )]
+    pub promotion: Box<Account<'info, Promotion>>,
+    /// the seller funds the promotion transferring tokens to this vault
+    #[account(
+        init,
+        payer = signer,
+        seeds = [
+            b"promotion_vault".as_ref(),
+            promotion.key().as_ref(),
+        ],
+        bump,
+        token::mint = payment_mint,
+        token::authority = promotion,
+        token::token_program = token_program,
+    )]
+    pub promotion_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn handler<'info>(ctx: Context<InitPromotion>, params: InitPromotionParams) -> Result<()> {
+    if params.buyer_reward > 10000 {
+        return Err(ErrorCode::IncorrectFee.into());
+    }
+
+    if params.end <= params.start || params.end <= Clock::get()?.unix_timestamp {
+        return Err(ErrorCode::IncorrectCampaignDates.into());
+    }
+
+    (*ctx.accounts.promotion).product = ctx.accounts.product.key();
+    (*ctx.accounts.promotion).id = params.id;
+    (*ctx.accounts.promotion).authority = ctx.accounts.signer.key();
+    (*ctx.accounts.promotion).vault = ctx.accounts.promotion_vault.key();
+    (*ctx.accounts.promotion).start = params.start;
+    (*ctx.accounts.promotion).end = params.end;
+    (*ctx.accounts.promotion).buyer_reward = params.buyer_reward;
+    (*ctx.accounts.promotion).bump = ctx.bumps.promotion;
+
+    Ok(())
+}
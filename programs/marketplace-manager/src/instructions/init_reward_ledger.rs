@@ -0,0 +1,58 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::TokenAccount,
+};
+
+#[derive(Accounts)]
+pub struct InitRewardLedger<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [
+            b"marketplace".as_ref(),
+            marketplace.authority.as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    #[account(
+        seeds = [
+            b"reward".as_ref(),
+            signer.key().as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump = reward.bump,
+    )]
+    pub reward: Account<'info, Reward>,
+    #[account(
+        constraint = reward_vault.owner == reward.key()
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = signer,
+        space = REWARD_LEDGER_SIZE,
+        seeds = [
+            b"reward_ledger".as_ref(),
+            reward_vault.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub reward_ledger: Account<'info, RewardLedger>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'info>(ctx: Context<InitRewardLedger>) -> Result<()> {
+    (*ctx.accounts.reward_ledger).reward = ctx.accounts.reward.key();
+    (*ctx.accounts.reward_ledger).vault = ctx.accounts.reward_vault.key();
+    (*ctx.accounts.reward_ledger).locked = 0;
+    (*ctx.accounts.reward_ledger).vesting_start = 0;
+    (*ctx.accounts.reward_ledger).bump = ctx.bumps.reward_ledger;
+    (*ctx.accounts.reward_ledger).cliff_end = 0;
+    (*ctx.accounts.reward_ledger).vesting_duration = 0;
+
+    Ok(())
+}
@@ -1,6 +1,5 @@
 use {
     crate::state::*,
-    crate::error::ErrorCode,
     anchor_lang::prelude::*,
     anchor_spl::{
         associated_token::AssociatedToken,
@@ -31,10 +30,6 @@ pub struct InitRewardVault<'info> {
         bump = reward.bump,
     )]
     pub reward: Account<'info, Reward>,
-    #[account(
-        constraint = reward_mint.key() == marketplace.rewards_config.reward_mint 
-            @ ErrorCode::IncorrectMint
-    )]
     pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
     #[account(
         init,
@@ -0,0 +1,148 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    crate::utils::{assert_not_paused, assert_resale_fees, get_fees_config, load_payment_mint_config, transfer_checked_with_hook},
+    anchor_lang::prelude::*,
+    anchor_spl::{
+        token_interface::{
+            Mint,
+            TokenAccount,
+            TokenInterface,
+        },
+        token_2022::ID as TokenProgram2022,
+    },
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ListResaleParams {
+    pub amount: u64,
+    pub unit_price: u64,
+}
+
+#[derive(Accounts)]
+pub struct ListResale<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [
+            b"marketplace".as_ref(),
+            marketplace.authority.as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    /// CHECK: validated with seeds, it is only deserialized if the guardian has been initialized
+    #[account(
+        seeds = [b"guardian".as_ref()],
+        bump,
+    )]
+    pub guardian: UncheckedAccount<'info>,
+    #[account(
+        seeds = [
+            b"product".as_ref(),
+            product.id.as_ref(),
+        ],
+        bump = product.bumps.bump,
+        constraint = product.marketplace == marketplace.key()
+            @ ErrorCode::IncorrectMarketplace,
+    )]
+    pub product: Box<Account<'info, Product>>,
+    /// CHECK: validated with seeds, it is only deserialized if the payment mint has been added
+    #[account(
+        seeds = [
+            b"payment_mint".as_ref(),
+            marketplace.key().as_ref(),
+            product.seller_config.payment_mint.as_ref(),
+        ],
+        bump,
+    )]
+    pub payment_mint_config: UncheckedAccount<'info>,
+    #[account(
+        seeds = [
+            b"product_mint".as_ref(),
+            product.key().as_ref(),
+        ],
+        bump = product.bumps.mint_bump,
+        constraint = product_mint.key() == product.product_mint
+            @ ErrorCode::IncorrectMint,
+    )]
+    pub product_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = signer,
+        space = LISTING_SIZE,
+        seeds = [
+            b"listing".as_ref(),
+            signer.key().as_ref(),
+            product.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub listing: Box<Account<'info, Listing>>,
+    #[account(
+        init,
+        payer = signer,
+        seeds = [
+            b"listing_vault".as_ref(),
+            listing.key().as_ref(),
+        ],
+        bump,
+        token::mint = product_mint,
+        token::authority = listing,
+        token::token_program = token_program,
+    )]
+    pub listing_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = reseller_token_vault.owner == signer.key()
+            @ ErrorCode::IncorrectAuthority,
+        constraint = reseller_token_vault.mint == product.product_mint
+            @ ErrorCode::IncorrectATA,
+    )]
+    pub reseller_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    #[account(address = TokenProgram2022 @ ErrorCode::IncorrectTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, ListResale<'info>>, params: ListResaleParams) -> Result<()> {
+    assert_not_paused(&ctx.accounts.marketplace, &ctx.accounts.guardian)?;
+
+    if !ctx.accounts.marketplace.token_config.transferable {
+        return Err(ErrorCode::NonTransferableProduct.into());
+    }
+
+    if params.amount == 0 {
+        return Err(ErrorCode::IncorrectAmount.into());
+    }
+
+    let fees_config = get_fees_config(
+        &ctx.accounts.marketplace.fees_config,
+        &load_payment_mint_config(&ctx.accounts.payment_mint_config)?,
+    );
+    assert_resale_fees(&fees_config, ctx.accounts.product.seller_config.royalty)?;
+
+    (*ctx.accounts.listing).authority = ctx.accounts.signer.key();
+    (*ctx.accounts.listing).product = ctx.accounts.product.key();
+    (*ctx.accounts.listing).payment_mint = ctx.accounts.product.seller_config.payment_mint;
+    (*ctx.accounts.listing).unit_price = params.unit_price;
+    (*ctx.accounts.listing).amount = params.amount;
+    (*ctx.accounts.listing).bumps = ListingBumps {
+        bump: ctx.bumps.listing,
+        vault_bump: ctx.bumps.listing_vault,
+    };
+
+    transfer_checked_with_hook(
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.reseller_token_vault.to_account_info(),
+        ctx.accounts.product_mint.to_account_info(),
+        ctx.accounts.listing_vault.to_account_info(),
+        ctx.accounts.signer.to_account_info(),
+        ctx.remaining_accounts,
+        params.amount,
+        &[],
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use {
+    crate::state::*,
+    crate::utils::{load_legacy_account, migrate_account, migrated_access},
+    anchor_lang::{prelude::*, Discriminator},
+};
+
+#[derive(Accounts)]
+pub struct MigrateAccess<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [
+            b"marketplace".as_ref(),
+            marketplace.authority.as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    /// CHECK: validated with seeds, it is deserialized with its previous layout in the ix logic
+    #[account(
+        mut,
+        seeds = [
+            b"request".as_ref(),
+            signer.key().as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub request: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'info>(ctx: Context<MigrateAccess>) -> Result<()> {
+    let legacy = load_legacy_account::<AccessV0>(
+        &ctx.accounts.request,
+        Access::DISCRIMINATOR,
+        ACCESS_V0_SIZE,
+    )?;
+
+    migrate_account(
+        &ctx.accounts.request,
+        &ctx.accounts.signer,
+        &ctx.accounts.system_program,
+        ACCESS_SIZE,
+        &migrated_access(legacy, ctx.accounts.marketplace.key(), Clock::get()?.unix_timestamp),
+    )
+}
@@ -0,0 +1,74 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    crate::utils::{load_legacy_account, migrate_account, migrated_campaign, migrated_marketplace, LEGACY_CAMPAIGN_ID},
+    anchor_lang::{prelude::*, Discriminator},
+};
+
+#[derive(Accounts)]
+pub struct MigrateMarketplace<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    /// CHECK: validated with seeds, it is deserialized with its previous layout in the ix logic
+    #[account(
+        mut,
+        seeds = [
+            b"marketplace".as_ref(),
+            signer.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub marketplace: UncheckedAccount<'info>,
+    /// required when the marketplace had rewards enabled, they keep being given by this campaign
+    #[account(
+        init,
+        payer = signer,
+        space = REWARD_CAMPAIGN_SIZE,
+        seeds = [
+            b"campaign".as_ref(),
+            marketplace.key().as_ref(),
+            LEGACY_CAMPAIGN_ID.as_ref(),
+        ],
+        bump,
+    )]
+    pub campaign: Option<Box<Account<'info, RewardCampaign>>>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'info>(ctx: Context<MigrateMarketplace>, campaign_end: i64) -> Result<()> {
+    let legacy = load_legacy_account::<MarketplaceV0>(
+        &ctx.accounts.marketplace,
+        Marketplace::DISCRIMINATOR,
+        MARKETPLACE_V0_SIZE,
+    )?;
+    let null_bump = Pubkey::find_program_address(&[b"null".as_ref()], ctx.program_id).1;
+
+    // the rewards of the marketplace are given by a campaign from now until the end chosen by the authority
+    match (legacy.rewards_enabled, ctx.accounts.campaign.as_mut()) {
+        (true, Some(campaign)) => {
+            let now = Clock::get()?.unix_timestamp;
+            if campaign_end <= now {
+                return Err(ErrorCode::IncorrectCampaignDates.into());
+            }
+
+            ***campaign = migrated_campaign(
+                &legacy,
+                ctx.accounts.marketplace.key(),
+                now,
+                campaign_end,
+                ctx.bumps.campaign,
+            );
+        },
+        (true, None) => return Err(ErrorCode::OptionalAccountNotProvided.into()),
+        (false, Some(_)) => return Err(ErrorCode::IncorrectLegacyCampaign.into()),
+        (false, None) => {},
+    }
+
+    migrate_account(
+        &ctx.accounts.marketplace,
+        &ctx.accounts.signer,
+        &ctx.accounts.system_program,
+        MARKETPLACE_SIZE,
+        &migrated_marketplace(legacy, null_bump),
+    )
+}
@@ -0,0 +1,45 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    crate::utils::{cmp_pubkeys, load_legacy_account, migrate_account, migrated_product},
+    anchor_lang::{prelude::*, Discriminator},
+};
+
+#[derive(Accounts)]
+pub struct MigrateProduct<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    /// CHECK: deserialized with its previous layout in the ix logic, the seeds depend on the id stored in it
+    #[account(mut)]
+    pub product: UncheckedAccount<'info>,
+    /// the marketplace where the product is sold, it has to be migrated first
+    #[account(
+        seeds = [
+            b"marketplace".as_ref(),
+            marketplace.authority.as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'info>(ctx: Context<MigrateProduct>) -> Result<()> {
+    let legacy = load_legacy_account::<ProductV0>(
+        &ctx.accounts.product,
+        Product::DISCRIMINATOR,
+        PRODUCT_V0_SIZE,
+    )?;
+
+    if !cmp_pubkeys(&legacy.authority, &ctx.accounts.signer.key()) {
+        return Err(ErrorCode::IncorrectAuthority.into());
+    }
+
+    migrate_account(
+        &ctx.accounts.product,
+        &ctx.accounts.signer,
+        &ctx.accounts.system_program,
+        PRODUCT_SIZE,
+        &migrated_product(legacy, ctx.accounts.marketplace.key()),
+    )
+}
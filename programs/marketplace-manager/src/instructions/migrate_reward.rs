@@ -0,0 +1,47 @@
+use {
+    crate::state::*,
+    crate::utils::{load_legacy_account, migrate_account, migrated_reward},
+    anchor_lang::{prelude::*, Discriminator},
+};
+
+#[derive(Accounts)]
+pub struct MigrateReward<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [
+            b"marketplace".as_ref(),
+            marketplace.authority.as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    /// CHECK: validated with seeds, it is deserialized with its previous layout in the ix logic
+    #[account(
+        mut,
+        seeds = [
+            b"reward".as_ref(),
+            signer.key().as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub reward: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'info>(ctx: Context<MigrateReward>) -> Result<()> {
+    let legacy = load_legacy_account::<RewardV0>(
+        &ctx.accounts.reward,
+        Reward::DISCRIMINATOR,
+        REWARD_V0_SIZE,
+    )?;
+
+    migrate_account(
+        &ctx.accounts.reward,
+        &ctx.accounts.signer,
+        &ctx.accounts.system_program,
+        REWARD_SIZE,
+        &migrated_reward(legacy),
+    )
+}
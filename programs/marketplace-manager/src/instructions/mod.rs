@@ -1,33 +1,95 @@
 pub mod accept_access;
+pub mod add_payment_mint;
 pub mod airdrop_access;
+pub mod block_wallet;
+pub mod buy_resale;
+pub mod cancel_resale;
+pub mod close_campaign;
+pub mod deny_access;
+pub mod edit_blocklist;
 pub mod edit_marketplace;
 pub mod edit_product;
+pub mod expire_access;
+pub mod fund_bounty;
+pub mod init_blocklist;
 pub mod init_bounty;
+pub mod init_campaign;
+pub mod init_guardian;
 pub mod init_marketplace;
+pub mod init_points_mint;
 pub mod init_product_tree;
 pub mod init_product;
+pub mod init_promotion;
 pub mod init_reward;
+pub mod init_reward_ledger;
 pub mod init_reward_vault;
+pub mod list_resale;
+pub mod migrate_access;
+pub mod migrate_marketplace;
+pub mod migrate_product;
+pub mod migrate_reward;
+pub mod pause_marketplace;
+pub mod redeem;
 pub mod register_buy_cnft;
 pub mod register_buy_fungible;
 pub mod register_buy;
+pub mod register_referral;
+pub mod remove_payment_mint;
+pub mod renew_access;
 pub mod request_access;
+pub mod revoke_access;
+pub mod set_guardian_pause;
+pub mod spend_points;
+pub mod unblock_wallet;
 pub mod update_tree;
+pub mod withdraw_bounty;
+pub mod withdraw_promotion;
 pub mod withdraw_reward;
 
 pub use accept_access::*;
+pub use add_payment_mint::*;
 pub use airdrop_access::*;
+pub use block_wallet::*;
+pub use buy_resale::*;
+pub use cancel_resale::*;
+pub use close_campaign::*;
+pub use deny_access::*;
+pub use edit_blocklist::*;
 pub use edit_marketplace::*;
 pub use edit_product::*;
+pub use expire_access::*;
+pub use fund_bounty::*;
 pub use init_product_tree::*;
+pub use init_blocklist::*;
 pub use init_bounty::*;
+pub use init_campaign::*;
+pub use init_guardian::*;
 pub use init_marketplace::*;
+pub use init_points_mint::*;
 pub use init_product::*;
+pub use init_promotion::*;
 pub use init_reward::*;
+pub use init_reward_ledger::*;
 pub use init_reward_vault::*;
+pub use list_resale::*;
+pub use migrate_access::*;
+pub use migrate_marketplace::*;
+pub use migrate_product::*;
+pub use migrate_reward::*;
+pub use pause_marketplace::*;
+pub use redeem::*;
 pub use register_buy_cnft::*;
 pub use register_buy_fungible::*;
 pub use register_buy::*;
+pub use register_referral::*;
+pub use remove_payment_mint::*;
+pub use renew_access::*;
 pub use request_access::*;
+pub use revoke_access::*;
+pub use set_guardian_pause::*;
+pub use spend_points::*;
+pub use unblock_wallet::*;
 pub use update_tree::*;
+pub use withdraw_bounty::*;
+pub use withdraw_promotion::*;
 pub use withdraw_reward::*;
\ No newline at end of file
@@ -0,0 +1,27 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct PauseMarketplace<'info> {
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"marketplace".as_ref(),
+            signer.key().as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+        constraint = signer.key() == marketplace.authority
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+}
+
+pub fn handler<'info>(ctx: Context<PauseMarketplace>, paused: bool) -> Result<()> {
+    (*ctx.accounts.marketplace).paused = paused;
+
+    Ok(())
+}
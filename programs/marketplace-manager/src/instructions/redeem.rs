@@ -0,0 +1,109 @@
+use {
+    crate::{
+        state::*,
+        events::RedemptionEvent,
+        error::ErrorCode,
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::{
+        token_interface::{
+            Mint,
+            TokenAccount,
+            TokenInterface,
+            Burn,
+            burn,
+        },
+        token_2022::ID as TokenProgram2022,
+    },
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RedeemParams {
+    pub id: [u8; 16],
+    pub amount: u64,
+    pub reference: Option<[u8; 32]>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: RedeemParams)]
+pub struct Redeem<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [
+            b"product".as_ref(),
+            product.id.as_ref(),
+        ],
+        bump = product.bumps.bump,
+    )]
+    pub product: Box<Account<'info, Product>>,
+    #[account(
+        mut,
+        constraint = product_mint.key() == product.product_mint
+            @ ErrorCode::IncorrectMint,
+    )]
+    pub product_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        constraint = buyer_token_vault.owner == signer.key()
+            @ ErrorCode::IncorrectAuthority,
+        constraint = buyer_token_vault.mint == product.product_mint
+            @ ErrorCode::IncorrectATA,
+    )]
+    pub buyer_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = signer,
+        space = REDEMPTION_SIZE,
+        seeds = [
+            b"redemption".as_ref(),
+            signer.key().as_ref(),
+            product.key().as_ref(),
+            params.id.as_ref(),
+        ],
+        bump,
+    )]
+    pub redemption: Box<Account<'info, Redemption>>,
+    pub system_program: Program<'info, System>,
+    #[account(address = TokenProgram2022 @ ErrorCode::IncorrectTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn handler<'info>(ctx: Context<Redeem>, params: RedeemParams) -> Result<()> {
+    if params.amount == 0 {
+        return Err(ErrorCode::IncorrectAmount.into());
+    }
+
+    burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.product_mint.to_account_info(),
+                from: ctx.accounts.buyer_token_vault.to_account_info(),
+                authority: ctx.accounts.signer.to_account_info(),
+            },
+        ),
+        params.amount,
+    ).map_err(|_| ErrorCode::BurnError)?;
+
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    (*ctx.accounts.redemption).authority = ctx.accounts.signer.key();
+    (*ctx.accounts.redemption).product = ctx.accounts.product.key();
+    (*ctx.accounts.redemption).id = params.id;
+    (*ctx.accounts.redemption).amount = params.amount;
+    (*ctx.accounts.redemption).timestamp = timestamp;
+    (*ctx.accounts.redemption).reference = params.reference;
+    (*ctx.accounts.redemption).bump = ctx.bumps.redemption;
+
+    emit!(RedemptionEvent {
+        redemption: ctx.accounts.redemption.key(),
+        product: ctx.accounts.product.key(),
+        buyer: ctx.accounts.signer.key(),
+        amount: params.amount,
+        timestamp,
+        reference: params.reference,
+    });
+
+    Ok(())
+}
@@ -9,9 +9,9 @@ use {
         system_program::System,
     },    
     anchor_spl::{
+        metadata::MetadataAccount,
         token_interface::{MintTo, Mint, TokenInterface, TokenAccount},
-        token_2022::mint_to,
-        token::{transfer, Transfer},
+        token_2022::{mint_to, thaw_account, ThawAccount, ID as TokenProgram2022},
     },
     spl_token::native_mint::ID as NativeMint
 };
@@ -41,6 +41,12 @@ pub struct RegisterBuyToken<'info> {
         bump = marketplace.bumps.bump,
     )]
     pub marketplace: Box<Account<'info, Marketplace>>,
+    /// CHECK: validated with seeds, it is only deserialized if the guardian has been initialized
+    #[account(
+        seeds = [b"guardian".as_ref()],
+        bump,
+    )]
+    pub guardian: UncheckedAccount<'info>,
     #[account(
         mut,
         seeds = [
@@ -48,8 +54,50 @@ pub struct RegisterBuyToken<'info> {
             product.id.as_ref(),
         ],
         bump = product.bumps.bump,
+        constraint = product.marketplace == marketplace.key()
+            @ ErrorCode::IncorrectMarketplace,
     )]
     pub product: Box<Account<'info, Product>>,
+    /// CHECK: validated with seeds, sellers that got access before the access records existed do not have one
+    #[account(
+        seeds = [
+            b"seller_access".as_ref(),
+            product.authority.as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub seller_access: UncheckedAccount<'info>,
+    /// CHECK: validated with seeds, the buyer is blocked if it exists
+    #[account(
+        seeds = [
+            b"blocked_wallet".as_ref(),
+            marketplace.key().as_ref(),
+            signer.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub blocked_buyer: UncheckedAccount<'info>,
+    /// CHECK: validated with seeds, the seller is blocked if it exists
+    #[account(
+        seeds = [
+            b"blocked_wallet".as_ref(),
+            marketplace.key().as_ref(),
+            product.authority.as_ref(),
+        ],
+        bump,
+    )]
+    pub blocked_seller: UncheckedAccount<'info>,
+    /// required when the product has a buyer gate based on tokens, mint validated in the ix logic
+    #[account(
+        constraint = buyer_gate_vault.owner == signer.key()
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub buyer_gate_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// required when the product is gated by a collection, mint of the NFT held in the buyer gate vault
+    pub buyer_gate_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// required when the product is gated by a collection, metadata of the NFT held in the buyer gate vault
+    pub buyer_gate_metadata: Option<Box<Account<'info, MetadataAccount>>>,
     #[account(
         mut,
         seeds = [
@@ -66,6 +114,16 @@ pub struct RegisterBuyToken<'info> {
             @ ErrorCode::IncorrectMint,
     )]
     pub payment_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: validated with seeds, it is only deserialized if the payment mint has been added
+    #[account(
+        seeds = [
+            b"payment_mint".as_ref(),
+            marketplace.key().as_ref(),
+            payment_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub payment_mint_config: UncheckedAccount<'info>,
     #[account(
         mut,
         constraint = buyer_token_vault.owner == signer.key()
@@ -99,6 +157,18 @@ pub struct RegisterBuyToken<'info> {
             @ ErrorCode::IncorrectATA,
     )]
     pub marketplace_transfer_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [
+            b"campaign".as_ref(),
+            marketplace.key().as_ref(),
+            campaign.id.as_ref(),
+        ],
+        bump = campaign.bump,
+    )]
+    pub campaign: Option<Box<Account<'info, RewardCampaign>>>,
+    /// required when the campaign pays the rewards in its own mint
+    pub payout_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
     // this account holds the reward tokens
     #[account(mut)]
     pub bounty_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
@@ -113,12 +183,17 @@ pub struct RegisterBuyToken<'info> {
         bump = seller_reward.bump
     )]
     pub seller_reward: Option<Account<'info, Reward>>,
+    #[account(mut)]
+    pub seller_reward_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     #[account(
         mut,
-        constraint = seller_reward_vault.mint == payment_mint.key() 
-            @ ErrorCode::IncorrectATA,
+        seeds = [
+            b"reward_ledger".as_ref(),
+            seller_reward_ledger.vault.as_ref(),
+        ],
+        bump = seller_reward_ledger.bump,
     )]
-    pub seller_reward_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub seller_reward_ledger: Option<Box<Account<'info, RewardLedger>>>,
     // authority checked in ix logic
     #[account(
         mut,
@@ -130,23 +205,154 @@ pub struct RegisterBuyToken<'info> {
         bump = buyer_reward.bump,
     )]
     pub buyer_reward: Option<Account<'info, Reward>>,
+    #[account(mut)]
+    pub buyer_reward_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     #[account(
         mut,
-        constraint = buyer_reward_vault.mint == payment_mint.key() 
-            @ ErrorCode::IncorrectATA,
+        seeds = [
+            b"reward_ledger".as_ref(),
+            buyer_reward_ledger.vault.as_ref(),
+        ],
+        bump = buyer_reward_ledger.bump,
     )]
-    pub buyer_reward_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub buyer_reward_ledger: Option<Box<Account<'info, RewardLedger>>>,
+    /// receives the buyer bonus when the marketplace pays instant cashback
+    #[account(
+        mut,
+        constraint = buyer_cashback_vault.owner == signer.key()
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub buyer_cashback_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// CHECK: validated with seeds, it is only deserialized if the buyer registered a referral
+    #[account(
+        seeds = [
+            b"referral".as_ref(),
+            signer.key().as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub referral: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"reward".as_ref(),
+            referrer_reward.authority.as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump = referrer_reward.bump
+    )]
+    pub referrer_reward: Option<Account<'info, Reward>>,
+    #[account(mut)]
+    pub referrer_reward_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [
+            b"reward_ledger".as_ref(),
+            referrer_reward_ledger.vault.as_ref(),
+        ],
+        bump = referrer_reward_ledger.bump,
+    )]
+    pub referrer_reward_ledger: Option<Box<Account<'info, RewardLedger>>>,
+    #[account(
+        mut,
+        constraint = seller_points_vault.owner == product.authority
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub seller_points_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        constraint = buyer_points_vault.owner == signer.key()
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub buyer_points_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// required when the campaign pays in points and the buyer was referred, validated in the ix logic
+    #[account(mut)]
+    pub referrer_points_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        close = signer,
+        seeds = [
+            b"points_discount".as_ref(),
+            signer.key().as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump = points_discount.bump,
+    )]
+    pub points_discount: Option<Box<Account<'info, PointsDiscount>>>,
+    #[account(
+        seeds = [
+            b"promotion".as_ref(),
+            product.key().as_ref(),
+            promotion.id.as_ref(),
+        ],
+        bump = promotion.bump,
+    )]
+    pub promotion: Option<Box<Account<'info, Promotion>>>,
+    /// validated in the ix logic against the vault stored in the promotion
+    #[account(mut)]
+    pub promotion_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        constraint = buyer_promotion_vault.owner == signer.key()
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub buyer_promotion_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     pub system_program: Program<'info, System>,
     pub token_program: Interface<'info, TokenInterface>,
+    #[account(address = TokenProgram2022 @ ErrorCode::IncorrectTokenProgram)]
+    pub token_program_2022: Option<Interface<'info, TokenInterface>>,
+    /// required when the campaign pays the rewards in its own mint, it is the program that owns the payout mint
+    pub payout_token_program: Option<Interface<'info, TokenInterface>>,
 }
 
-pub fn handler<'info>(ctx: Context<RegisterBuyToken>, amount: u32) -> Result<()> {
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, RegisterBuyToken<'info>>, amount: u32, proof: Vec<[u8; 32]>, payment_hook_accounts: u8) -> Result<()> {
+    let (payment_hook_accounts, payout_hook_accounts) = split_hook_accounts(ctx.remaining_accounts, payment_hook_accounts)?;
     let total_amount = ctx.accounts.product.seller_config.product_price
         .checked_mul(amount.into()).ok_or(ErrorCode::NumericalOverflow)?;
+    // points spent by the buyer reduce the marketplace fee, the discount account is closed with this purchase
+    let discount = ctx.accounts.points_discount.as_ref()
+        .map_or(0, |points_discount| points_discount.discount);
     let marketplace = &ctx.accounts.marketplace;
 
-    // payment and fees
-    if cmp_pubkeys(&ctx.accounts.payment_mint.key(), &NativeMint) {
+    assert_not_paused(marketplace, &ctx.accounts.guardian)?;
+    let payment_mint_config = load_payment_mint_config(&ctx.accounts.payment_mint_config)?;
+    assert_payment_mint(
+        &marketplace.fees_config,
+        &payment_mint_config,
+        ctx.accounts.payment_mint.decimals,
+    )?;
+    let fees_config = get_fees_config(&marketplace.fees_config, &payment_mint_config);
+
+    assert_not_blocked(
+        &marketplace.permission_config,
+        &ctx.accounts.blocked_buyer,
+    )?;
+    // blocked sellers can not sell the products they created before being blocked
+    assert_not_blocked(
+        &marketplace.permission_config,
+        &ctx.accounts.blocked_seller,
+    )?;
+
+    // external access gates do not have an access record
+    if !marketplace.permission_config.permissionless && marketplace.permission_config.access_gate == AccessGate::AccessMint {
+        assert_seller_access(&ctx.accounts.seller_access)?;
+    }
+
+    if let Some(buyer_gate) = &ctx.accounts.product.buyer_gate {
+        assert_access_gate(
+            buyer_gate,
+            &marketplace.permission_config.access_mint,
+            &ctx.accounts.signer.key(),
+            ctx.accounts.buyer_gate_vault.as_deref(),
+            ctx.accounts.buyer_gate_mint.as_deref(),
+            ctx.accounts.buyer_gate_metadata.as_deref(),
+            &proof,
+        )?;
+    }
+
+    // payment and fees, the rewards are calculated from what the payment is worth net of the transfer fee
+    let net_amount = if cmp_pubkeys(&ctx.accounts.payment_mint.key(), &NativeMint) {
         let marketplace_auth = ctx.accounts.marketplace_auth.as_ref()
             .ok_or(ErrorCode::OptionalAccountNotProvided)?;
         let seller = ctx.accounts.seller.as_ref()
@@ -157,10 +363,13 @@ pub fn handler<'info>(ctx: Context<RegisterBuyToken>, amount: u32) -> Result<()>
             ctx.accounts.signer.to_account_info(),
             marketplace_auth.to_account_info(),
             seller.to_account_info(),
-            marketplace.fees_config.clone(),
+            fees_config.clone(),
             ctx.accounts.product.seller_config.payment_mint,
             total_amount,
+            discount,
         )?;
+
+        total_amount
     } else {
         let marketplace_transfer_vault = ctx.accounts.marketplace_transfer_vault.as_ref()
             .ok_or(ErrorCode::OptionalAccountNotProvided)?;
@@ -174,88 +383,86 @@ pub fn handler<'info>(ctx: Context<RegisterBuyToken>, amount: u32) -> Result<()>
             ctx.accounts.signer.to_account_info(),
             marketplace_transfer_vault.to_account_info(),
             seller_transfer_vault.to_account_info(),
-                buyer_transfer_vault.to_account_info(),
-                marketplace.fees_config.clone(),
-            ctx.accounts.product.seller_config.payment_mint,
-            total_amount,            
-        )?;
-    }
+            buyer_transfer_vault.to_account_info(),
+            ctx.accounts.payment_mint.to_account_info(),
+            payment_hook_accounts,
+            fees_config.clone(),
+            total_amount,
+            discount,
+        )?
+    };
 
     // rewards
-    if is_rewards_active(
-        marketplace.rewards_config.clone(), 
-        ctx.accounts.payment_mint.key(),
-        ctx.program_id.key(),
-    ) {
-        let seller_reward = ctx.accounts.seller_reward.as_ref()
-            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
-        let buyer_reward = ctx.accounts.buyer_reward.as_ref()
-            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+    let now = Clock::get()?.unix_timestamp;
+    let mut reward_accounts = RewardAccounts {
+        marketplace,
+        seller: ctx.accounts.product.authority,
+        buyer: ctx.accounts.signer.key(),
+        payment_mint: &ctx.accounts.payment_mint,
+        campaign: ctx.accounts.campaign.as_deref_mut(),
+        payout_mint: ctx.accounts.payout_mint.as_deref(),
+        bounty_vault: ctx.accounts.bounty_vault.as_deref(),
+        seller_reward: ctx.accounts.seller_reward.as_mut(),
+        seller_reward_vault: ctx.accounts.seller_reward_vault.as_deref(),
+        seller_reward_ledger: ctx.accounts.seller_reward_ledger.as_deref_mut(),
+        buyer_reward: ctx.accounts.buyer_reward.as_mut(),
+        buyer_reward_vault: ctx.accounts.buyer_reward_vault.as_deref(),
+        buyer_reward_ledger: ctx.accounts.buyer_reward_ledger.as_deref_mut(),
+        buyer_cashback_vault: ctx.accounts.buyer_cashback_vault.as_deref(),
+        referral: load_referral(&ctx.accounts.referral)?,
+        referrer_reward: ctx.accounts.referrer_reward.as_mut(),
+        referrer_reward_vault: ctx.accounts.referrer_reward_vault.as_deref(),
+        referrer_reward_ledger: ctx.accounts.referrer_reward_ledger.as_deref_mut(),
+        seller_points_vault: ctx.accounts.seller_points_vault.as_deref(),
+        buyer_points_vault: ctx.accounts.buyer_points_vault.as_deref(),
+        referrer_points_vault: ctx.accounts.referrer_points_vault.as_deref(),
+        promotion: ctx.accounts.promotion.as_deref(),
+        promotion_vault: ctx.accounts.promotion_vault.as_deref(),
+        buyer_promotion_vault: ctx.accounts.buyer_promotion_vault.as_deref(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        token_program_2022: ctx.accounts.token_program_2022.as_ref()
+            .map(|token_program_2022| token_program_2022.to_account_info()),
+        payout_token_program: ctx.accounts.payout_token_program.as_ref()
+            .map(|payout_token_program| payout_token_program.to_account_info()),
+        payment_hook_accounts,
+        payout_hook_accounts,
+    };
+    settle_rewards(&mut reward_accounts, net_amount, now)?;
+    pay_promotion(&reward_accounts, net_amount, now)?;
 
-        assert_authority(&seller_reward.key(), &ctx.accounts.product.authority)?;
-        assert_authority(&buyer_reward.key(), &ctx.accounts.signer.key())?;
-
-        let seller_bonus = (marketplace.rewards_config.seller_reward as u128)
-            .checked_mul(ctx.accounts.product.seller_config.product_price as u128)
-            .ok_or(ErrorCode::NumericalOverflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::NumericalOverflow)? as u64;
-
-        let buyer_bonus = (marketplace.rewards_config.buyer_reward as u128)
-            .checked_mul(ctx.accounts.product.seller_config.product_price as u128)
-            .ok_or(ErrorCode::NumericalOverflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::NumericalOverflow)? as u64;
-
-        let marketplace_seeds = &[
-            "marketplace".as_ref(),
-            marketplace.authority.as_ref(),
-            &[marketplace.bumps.bump],
-        ];
-
-        let seller_reward_vault = ctx.accounts.seller_reward_vault.as_ref()
-            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
-        let buyer_reward_vault = ctx.accounts.buyer_reward_vault.as_ref()
-            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
-        let bounty_vault = ctx.accounts.bounty_vault.as_ref()
-            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+    let seeds = &[
+        b"product".as_ref(),
+        ctx.accounts.product.id.as_ref(),
+        &[ctx.accounts.product.bumps.bump],
+    ];
 
-        transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(), 
-                Transfer {
-                    from: bounty_vault.to_account_info(),
-                    to: seller_reward_vault.to_account_info(),
-                    authority: marketplace.to_account_info(),
-                },
-                &[&marketplace_seeds[..]],
-            ),
-            seller_bonus,
-        ).map_err(|_| ErrorCode::TransferError)?;
+    // product mints are Token-2022, the token program can belong to a legacy payment mint
+    let product_token_program = if cmp_pubkeys(&ctx.accounts.token_program.key(), &TokenProgram2022) {
+        ctx.accounts.token_program.to_account_info()
+    } else {
+        ctx.accounts.token_program_2022.as_ref()
+            .ok_or(ErrorCode::OptionalAccountNotProvided)?
+            .to_account_info()
+    };
 
-        transfer(
+    // product mints created with a frozen default account state are thawed once the buyer pays
+    if ctx.accounts.buyer_token_vault.is_frozen() {
+        thaw_account(
             CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(), 
-                Transfer {
-                    from: bounty_vault.to_account_info(),
-                    to: buyer_reward_vault.to_account_info(),
-                    authority: ctx.accounts.marketplace.to_account_info()
+                product_token_program.clone(),
+                ThawAccount {
+                    account: ctx.accounts.buyer_token_vault.to_account_info(),
+                    mint: ctx.accounts.product_mint.to_account_info(),
+                    authority: ctx.accounts.product.to_account_info(),
                 },
-                &[&marketplace_seeds[..]],
+                &[&seeds[..]],
             ),
-            buyer_bonus,
-        ).map_err(|_| ErrorCode::TransferError)?;
+        ).map_err(|_| ErrorCode::ThawAccountError)?;
     }
 
-    let seeds = &[
-        b"product".as_ref(),
-        ctx.accounts.product.id.as_ref(),
-        &[ctx.accounts.product.bumps.bump],
-    ];
-
     mint_to(
         CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
+            product_token_program,
             MintTo {
                 mint: ctx.accounts.product_mint.to_account_info(),
                 to: ctx.accounts.buyer_token_vault.to_account_info(),
@@ -0,0 +1,56 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    crate::utils::assert_not_paused,
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct RegisterReferral<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [
+            b"marketplace".as_ref(),
+            marketplace.authority.as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    /// CHECK: validated with seeds, it is only deserialized if the guardian has been initialized
+    #[account(
+        seeds = [b"guardian".as_ref()],
+        bump,
+    )]
+    pub guardian: UncheckedAccount<'info>,
+    #[account(
+        constraint = referrer.key() != signer.key()
+            @ ErrorCode::SelfReferral,
+    )]
+    pub referrer: SystemAccount<'info>,
+    #[account(
+        init,
+        payer = signer,
+        space = REFERRAL_SIZE,
+        seeds = [
+            b"referral".as_ref(),
+            signer.key().as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub referral: Account<'info, Referral>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'info>(ctx: Context<RegisterReferral>) -> Result<()> {
+    assert_not_paused(&ctx.accounts.marketplace, &ctx.accounts.guardian)?;
+
+    (*ctx.accounts.referral).authority = ctx.accounts.signer.key();
+    (*ctx.accounts.referral).marketplace = ctx.accounts.marketplace.key();
+    (*ctx.accounts.referral).referrer = ctx.accounts.referrer.key();
+    (*ctx.accounts.referral).created_at = Clock::get()?.unix_timestamp;
+    (*ctx.accounts.referral).bump = ctx.bumps.referral;
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct RemovePaymentMint<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [
+            b"marketplace".as_ref(),
+            signer.key().as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+        constraint = signer.key() == marketplace.authority
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    #[account(
+        mut,
+        seeds = [
+            b"payment_mint".as_ref(),
+            marketplace.key().as_ref(),
+            payment_mint_config.mint.as_ref(),
+        ],
+        bump = payment_mint_config.bump,
+        close = signer,
+    )]
+    pub payment_mint_config: Box<Account<'info, PaymentMintConfig>>,
+}
+
+pub fn handler<'info>(_ctx: Context<RemovePaymentMint>) -> Result<()> {
+    Ok(())
+}
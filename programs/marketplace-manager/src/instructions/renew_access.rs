@@ -0,0 +1,45 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    crate::utils::calculate_renewed_expiry,
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct RenewAccess<'info> {
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [
+            b"marketplace".as_ref(),
+            signer.key().as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+        constraint = signer.key() == marketplace.authority
+            @ErrorCode::IncorrectAuthority,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    #[account(
+        mut,
+        seeds = [
+            b"seller_access".as_ref(),
+            seller_access.authority.as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump = seller_access.bump,
+    )]
+    pub seller_access: Box<Account<'info, SellerAccess>>,
+}
+
+pub fn handler<'info>(ctx: Context<RenewAccess>, duration: i64) -> Result<()> {
+    if duration <= 0 {
+        return Err(ErrorCode::IncorrectAmount.into());
+    }
+
+    (*ctx.accounts.seller_access).expires_at = calculate_renewed_expiry(
+        ctx.accounts.seller_access.expires_at,
+        Clock::get()?.unix_timestamp,
+        duration,
+    )?;
+
+    Ok(())
+}
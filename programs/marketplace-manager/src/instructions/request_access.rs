@@ -1,8 +1,16 @@
 use {
     crate::state::*,
+    crate::error::ErrorCode,
     anchor_lang::prelude::*,
 };
 
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RequestAccessParams {
+    pub uri: String,
+    pub attestation: [u8; 32],
+    pub category: String,
+}
+
 #[derive(Accounts)]
 pub struct RequestAccess<'info> {
     #[account(mut)]
@@ -32,9 +40,18 @@ pub struct RequestAccess<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler<'info>(ctx: Context<RequestAccess>) -> Result<()> {
+pub fn handler<'info>(ctx: Context<RequestAccess>, params: RequestAccessParams) -> Result<()> {
+    if params.uri.len() > MAX_URI_LENGTH || params.category.len() > MAX_CATEGORY_LENGTH {
+        return Err(ErrorCode::StringTooLong.into());
+    }
+
     (*ctx.accounts.request).authority = ctx.accounts.signer.key();
+    (*ctx.accounts.request).marketplace = ctx.accounts.marketplace.key();
+    (*ctx.accounts.request).uri = params.uri;
+    (*ctx.accounts.request).attestation = params.attestation;
+    (*ctx.accounts.request).category = params.category;
     (*ctx.accounts.request).bump = ctx.bumps.request;
+    (*ctx.accounts.request).created_at = Clock::get()?.unix_timestamp;
     
     Ok(())
 }
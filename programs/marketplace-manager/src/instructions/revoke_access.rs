@@ -0,0 +1,152 @@
+use {
+    crate::state::*,
+    crate::utils::cmp_pubkeys,
+    crate::error::ErrorCode,
+    anchor_lang::{prelude::*, system_program::System},
+    anchor_spl::{
+        token_2022::{burn, Burn, freeze_account, FreezeAccount},
+        token_interface::{Mint, TokenInterface, TokenAccount},
+        token_2022::ID as TokenProgram2022,
+    },
+    spl_token_2022::extension::{
+        BaseStateWithExtensions,
+        StateWithExtensions,
+        permanent_delegate::PermanentDelegate,
+    },
+    spl_token_2022::state::Mint as Mint2022,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum RevokeMode {
+    /// The access token is burned using the permanent delegate of the access mint.
+    Burn,
+    /// The access vault is frozen using the freeze authority of the access mint.
+    Freeze,
+    /// Only the access record is revoked, for access mints that give the marketplace neither authority.
+    Record,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAccess<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub seller: SystemAccount<'info>,
+    #[account(
+        seeds = [
+            b"marketplace".as_ref(),
+            signer.key().as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+        constraint = signer.key() == marketplace.authority
+            @ErrorCode::IncorrectAuthority,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    #[account(
+        mut,
+        seeds = [
+            b"access_mint".as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump = marketplace.bumps.access_mint_bump,
+        constraint = access_mint.key() == marketplace.permission_config.access_mint
+            @ErrorCode::IncorrectMint
+    )]
+    pub access_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = access_mint,
+        associated_token::authority = seller,
+        associated_token::token_program = token_program
+    )]
+    pub access_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// sellers that got access before the records existed get one, so the revocation also stops their sales
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = SELLER_ACCESS_SIZE,
+        seeds = [
+            b"seller_access".as_ref(),
+            seller.key().as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub seller_access: Box<Account<'info, SellerAccess>>,
+    pub system_program: Program<'info, System>,
+    #[account(address = TokenProgram2022 @ ErrorCode::IncorrectTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn handler<'info>(ctx: Context<RevokeAccess>, mode: RevokeMode) -> Result<()> {
+    let signer_key = ctx.accounts.signer.key();
+    let marketplace_seeds = &[
+        b"marketplace".as_ref(),
+        signer_key.as_ref(),
+        &[ctx.accounts.marketplace.bumps.bump],
+    ];
+
+    (*ctx.accounts.seller_access).authority = ctx.accounts.seller.key();
+    (*ctx.accounts.seller_access).marketplace = ctx.accounts.marketplace.key();
+    (*ctx.accounts.seller_access).bump = ctx.bumps.seller_access;
+    (*ctx.accounts.seller_access).revoked = true;
+
+    let marketplace_key = ctx.accounts.marketplace.key();
+    let can_burn = {
+        let access_mint_info = ctx.accounts.access_mint.to_account_info();
+        let access_mint_data = access_mint_info.try_borrow_data()?;
+        let access_mint = StateWithExtensions::<Mint2022>::unpack(&access_mint_data)?;
+        access_mint.get_extension::<PermanentDelegate>()
+            .ok()
+            .and_then(|permanent_delegate| Option::<Pubkey>::from(permanent_delegate.delegate))
+            .is_some_and(|delegate| cmp_pubkeys(&delegate, &marketplace_key))
+    };
+    let can_freeze = Option::<Pubkey>::from(ctx.accounts.access_mint.freeze_authority)
+        .is_some_and(|freeze_authority| cmp_pubkeys(&freeze_authority, &marketplace_key));
+
+    // the access record stops the sales of the seller, the token is burned or frozen when the access mint
+    // gives the marketplace the permanent delegate or the freeze authority, tokens already burned or frozen are kept as they are
+    match mode {
+        RevokeMode::Burn => {
+            if !can_burn {
+                return Err(ErrorCode::RevokeModeNotSupported.into());
+            }
+
+            if ctx.accounts.access_vault.amount > 0 {
+                burn(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Burn {
+                            mint: ctx.accounts.access_mint.to_account_info(),
+                            from: ctx.accounts.access_vault.to_account_info(),
+                            authority: ctx.accounts.marketplace.to_account_info(),
+                        },
+                        &[&marketplace_seeds[..]],
+                    ),
+                    ctx.accounts.access_vault.amount,
+                ).map_err(|_| ErrorCode::BurnError)?;
+            }
+        }
+        RevokeMode::Freeze => {
+            if !can_freeze {
+                return Err(ErrorCode::RevokeModeNotSupported.into());
+            }
+
+            if !ctx.accounts.access_vault.is_frozen() {
+                freeze_account(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        FreezeAccount {
+                            account: ctx.accounts.access_vault.to_account_info(),
+                            mint: ctx.accounts.access_mint.to_account_info(),
+                            authority: ctx.accounts.marketplace.to_account_info(),
+                        },
+                        &[&marketplace_seeds[..]],
+                    ),
+                ).map_err(|_| ErrorCode::FreezeAccountError)?;
+            }
+        }
+        RevokeMode::Record => {}
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,24 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetGuardianPause<'info> {
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"guardian".as_ref()],
+        bump = guardian.bump,
+        constraint = signer.key() == guardian.authority
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub guardian: Box<Account<'info, Guardian>>,
+}
+
+pub fn handler<'info>(ctx: Context<SetGuardianPause>, paused: bool) -> Result<()> {
+    (*ctx.accounts.guardian).paused = paused;
+
+    Ok(())
+}
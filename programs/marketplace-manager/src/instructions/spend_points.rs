@@ -0,0 +1,115 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    crate::utils::{assert_not_paused, calculate_points_discount, get_fees_config, load_payment_mint_config},
+    anchor_lang::prelude::*,
+    anchor_spl::{
+        token_interface::{
+            Mint,
+            TokenAccount,
+            TokenInterface,
+            Burn,
+            burn,
+        },
+        token_2022::ID as TokenProgram2022,
+    },
+};
+
+#[derive(Accounts)]
+pub struct SpendPoints<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [
+            b"marketplace".as_ref(),
+            marketplace.authority.as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    /// CHECK: validated with seeds, it is only deserialized if the guardian has been initialized
+    #[account(
+        seeds = [b"guardian".as_ref()],
+        bump,
+    )]
+    pub guardian: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = marketplace.rewards_config.points_mint
+            @ ErrorCode::IncorrectMint,
+    )]
+    pub points_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        constraint = points_vault.owner == signer.key()
+            @ ErrorCode::IncorrectAuthority,
+        constraint = points_vault.mint == points_mint.key()
+            @ ErrorCode::IncorrectATA,
+    )]
+    pub points_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: mint the buyer will pay with, its fee override limits the discount
+    pub payment_mint: UncheckedAccount<'info>,
+    /// CHECK: validated with seeds, it is only deserialized if the payment mint has been added
+    #[account(
+        seeds = [
+            b"payment_mint".as_ref(),
+            marketplace.key().as_ref(),
+            payment_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub payment_mint_config: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = POINTS_DISCOUNT_SIZE,
+        seeds = [
+            b"points_discount".as_ref(),
+            signer.key().as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub points_discount: Box<Account<'info, PointsDiscount>>,
+    pub system_program: Program<'info, System>,
+    #[account(address = TokenProgram2022 @ ErrorCode::IncorrectTokenProgram)]
+    pub token_program_2022: Interface<'info, TokenInterface>,
+}
+
+pub fn handler<'info>(ctx: Context<SpendPoints>, points: u64) -> Result<()> {
+    assert_not_paused(&ctx.accounts.marketplace, &ctx.accounts.guardian)?;
+
+    let points_discount = ctx.accounts.marketplace.rewards_config.points_discount;
+    if points_discount == 0 {
+        return Err(ErrorCode::PointsNotEnabled.into());
+    }
+
+    // the marketplace funds the discount with its fee, so it is limited to the fee of the payment mint
+    let payment_mint_config = load_payment_mint_config(&ctx.accounts.payment_mint_config)?;
+    let fees_config = get_fees_config(&ctx.accounts.marketplace.fees_config, &payment_mint_config);
+    let discount = calculate_points_discount(
+        ctx.accounts.points_discount.discount,
+        points,
+        points_discount,
+        fees_config.fee,
+    )?;
+
+    burn(
+        CpiContext::new(
+            ctx.accounts.token_program_2022.to_account_info(),
+            Burn {
+                mint: ctx.accounts.points_mint.to_account_info(),
+                from: ctx.accounts.points_vault.to_account_info(),
+                authority: ctx.accounts.signer.to_account_info(),
+            },
+        ),
+        points,
+    ).map_err(|_| ErrorCode::BurnError)?;
+
+    (*ctx.accounts.points_discount).authority = ctx.accounts.signer.key();
+    (*ctx.accounts.points_discount).marketplace = ctx.accounts.marketplace.key();
+    (*ctx.accounts.points_discount).discount = discount;
+    (*ctx.accounts.points_discount).bump = ctx.bumps.points_discount;
+
+    Ok(())
+}
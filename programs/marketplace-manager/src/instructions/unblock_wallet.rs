@@ -0,0 +1,45 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct UnblockWallet<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [
+            b"marketplace".as_ref(),
+            marketplace.authority.as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    #[account(
+        seeds = [
+            b"blocklist".as_ref(),
+            marketplace.key().as_ref(),
+        ],
+        bump = blocklist.bump,
+        constraint = signer.key() == marketplace.authority || signer.key() == blocklist.delegate
+            @ErrorCode::IncorrectAuthority,
+    )]
+    pub blocklist: Box<Account<'info, Blocklist>>,
+    /// the signer gets the rent back
+    #[account(
+        mut,
+        close = signer,
+        seeds = [
+            b"blocked_wallet".as_ref(),
+            marketplace.key().as_ref(),
+            blocked_wallet.wallet.as_ref(),
+        ],
+        bump = blocked_wallet.bump,
+    )]
+    pub blocked_wallet: Box<Account<'info, BlockedWallet>>,
+}
+
+pub fn handler<'info>(_ctx: Context<UnblockWallet>) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    crate::utils::transfer_checked_with_hook,
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::{Mint, TokenInterface, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct WithdrawBounty<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [
+            b"marketplace".as_ref(),
+            signer.key().as_ref(),
+        ],
+        bump = marketplace.bumps.bump,
+        constraint = signer.key() == marketplace.authority
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub marketplace: Box<Account<'info, Marketplace>>,
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [
+            b"bounty_vault".as_ref(),
+            marketplace.key().as_ref(),
+            reward_mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub bounty_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = receiver_vault.owner == marketplace.authority
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub receiver_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawBounty<'info>>, amount: u64) -> Result<()> {
+    // the budget of the campaigns is paid from the bounty vaults until they are closed
+    if ctx.accounts.marketplace.rewards_config.open_campaigns > 0 {
+        return Err(ErrorCode::OpenCampaigns.into());
+    }
+
+    let marketplace_seeds = &[
+        b"marketplace".as_ref(),
+        ctx.accounts.marketplace.authority.as_ref(),
+        &[ctx.accounts.marketplace.bumps.bump],
+    ];
+
+    transfer_checked_with_hook(
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.bounty_vault.to_account_info(),
+        ctx.accounts.reward_mint.to_account_info(),
+        ctx.accounts.receiver_vault.to_account_info(),
+        ctx.accounts.marketplace.to_account_info(),
+        ctx.remaining_accounts,
+        amount,
+        &[&marketplace_seeds[..]],
+    )?;
+
+    Ok(())
+}
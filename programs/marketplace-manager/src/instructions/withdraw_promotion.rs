@@ -0,0 +1,91 @@
+use {
+    crate::state::*,
+    crate::error::ErrorCode,
+    crate::utils::transfer_checked_with_hook,
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::{
+        Mint,
+        TokenAccount,
+        TokenInterface,
+        CloseAccount,
+        close_account,
+    },
+};
+
+#[derive(Accounts)]
+pub struct WithdrawPromotion<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        close = signer,
+        seeds = [
+            b"promotion".as_ref(),
+            promotion.product.as_ref(),
+            promotion.id.as_ref(),
+        ],
+        bump = promotion.bump,
+        constraint = signer.key() == promotion.authority
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub promotion: Box<Account<'info, Promotion>>,
+    #[account(
+        mut,
+        address = promotion.vault
+            @ ErrorCode::IncorrectATA,
+    )]
+    pub promotion_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        constraint = payment_mint.key() == promotion_vault.mint
+            @ ErrorCode::IncorrectMint,
+    )]
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        constraint = receiver_vault.owner == signer.key()
+            @ ErrorCode::IncorrectAuthority,
+    )]
+    pub receiver_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawPromotion<'info>>) -> Result<()> {
+    if Clock::get()?.unix_timestamp < ctx.accounts.promotion.end {
+        return Err(ErrorCode::OpenPromotion.into());
+    }
+
+    let promotion_seeds = &[
+        b"promotion".as_ref(),
+        ctx.accounts.promotion.product.as_ref(),
+        ctx.accounts.promotion.id.as_ref(),
+        &[ctx.accounts.promotion.bump],
+    ];
+
+    if ctx.accounts.promotion_vault.amount > 0 {
+        transfer_checked_with_hook(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.promotion_vault.to_account_info(),
+            ctx.accounts.payment_mint.to_account_info(),
+            ctx.accounts.receiver_vault.to_account_info(),
+            ctx.accounts.promotion.to_account_info(),
+            ctx.remaining_accounts,
+            ctx.accounts.promotion_vault.amount,
+            &[&promotion_seeds[..]],
+        )?;
+    }
+
+    // the vault rent goes back to the seller together with the rent of the promotion
+    close_account(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.promotion_vault.to_account_info(),
+                destination: ctx.accounts.signer.to_account_info(),
+                authority: ctx.accounts.promotion.to_account_info(),
+            },
+            &[&promotion_seeds[..]],
+        ),
+    ).map_err(|_| ErrorCode::CloseAccountError)?;
+
+    Ok(())
+}
\ No newline at end of file
@@ -1,8 +1,8 @@
 use {
     crate::state::*,
     crate::error::ErrorCode,
+    crate::utils::{calculate_locked, transfer_checked_with_hook},
     anchor_lang::prelude::*,
-    anchor_spl::token::{transfer, Transfer},
     anchor_spl::token_interface::{Mint, TokenInterface, TokenAccount}
 };
 
@@ -45,13 +45,36 @@ pub struct WithdrawReward<'info> {
             @ ErrorCode::IncorrectMint,
     )]
     pub reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// required when the marketplace vests the rewards
+    #[account(
+        seeds = [
+            b"reward_ledger".as_ref(),
+            reward_vault.key().as_ref(),
+        ],
+        bump = reward_ledger.bump,
+    )]
+    pub reward_ledger: Option<Account<'info, RewardLedger>>,
     pub token_program: Interface<'info, TokenInterface>,   
 }
 
-pub fn handler<'info>(ctx: Context<WithdrawReward>) -> Result<()> {
-    if ctx.accounts.marketplace.rewards_config.rewards_enabled {
-        return Err(ErrorCode::OpenPromotion.into());
-    } 
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawReward<'info>>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let rewards_config = &ctx.accounts.marketplace.rewards_config;
+
+    // vested rewards can be withdrawn at any time, otherwise the campaigns have to end
+    let amount = if rewards_config.vesting_duration > 0 {
+        let reward_ledger = ctx.accounts.reward_ledger.as_ref()
+            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+        let locked = calculate_locked(reward_ledger, now)?;
+
+        ctx.accounts.reward_vault.amount.saturating_sub(locked)
+    } else {
+        if now < ctx.accounts.reward.withdrawable_at {
+            return Err(ErrorCode::OpenPromotion.into());
+        }
+
+        ctx.accounts.reward_vault.amount
+    };
 
     let signer_key = ctx.accounts.signer.key().to_bytes();
     let marketplace_key = ctx.accounts.marketplace.key().to_bytes();
@@ -62,18 +85,16 @@ pub fn handler<'info>(ctx: Context<WithdrawReward>) -> Result<()> {
         &[ctx.accounts.reward.bump],
     ];
 
-    transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.reward_vault.to_account_info(),
-                to: ctx.accounts.receiver_vault.to_account_info(),
-                authority: ctx.accounts.reward.to_account_info(),
-            },
-            &[&seeds[..]],
-        ),
-        ctx.accounts.reward_vault.amount,
-    ).map_err(|_| ErrorCode::TransferError)?;
+    transfer_checked_with_hook(
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.reward_vault.to_account_info(),
+        ctx.accounts.reward_mint.to_account_info(),
+        ctx.accounts.receiver_vault.to_account_info(),
+        ctx.accounts.reward.to_account_info(),
+        ctx.remaining_accounts,
+        amount,
+        &[&seeds[..]],
+    )?;
     
     Ok(())
 }
\ No newline at end of file
@@ -1,10 +1,12 @@
 pub mod state;
 pub mod utils;
 pub mod error;
+pub mod events;
 mod instructions;
 use {
     anchor_lang::prelude::*,
     instructions::*,
+    state::AccessGate,
 };
 
 declare_id!("brick5uEiJqSkfuAvMtKmq7kiuEVmbjVMiigyV51GRF");
@@ -14,35 +16,101 @@ pub mod marketplace_manager {
     use super::*;
 
     /// airdrop a token that allows users to create products in a specific marketplace
-    pub fn accept_access(ctx: Context<AcceptAccess>) -> Result<()> {
-        accept_access::handler(ctx)
+    pub fn accept_access(ctx: Context<AcceptAccess>, expires_at: Option<i64>) -> Result<()> {
+        accept_access::handler(ctx, expires_at)
     }
 
-    /// airdrop a token that allows users to create products in a specific marketplace
-    pub fn airdrop_access(ctx: Context<AirdropAccess>) -> Result<()> {
-        airdrop_access::handler(ctx)
+    /// marketplace authority accepts a payment mint or updates its settings
+    pub fn add_payment_mint(ctx: Context<AddPaymentMint>, params: AddPaymentMintParams) -> Result<()> {
+        add_payment_mint::handler(ctx, params)
+    }
+
+    /// airdrop a token that allows users to create products in a specific marketplace,
+    /// also used to give back the access to a seller that was revoked
+    pub fn airdrop_access(ctx: Context<AirdropAccess>, expires_at: Option<i64>) -> Result<()> {
+        airdrop_access::handler(ctx, expires_at)
+    }
+
+    /// marketplace authority or the blocklist delegate block a wallet, it can not sell nor buy while it is blocked
+    pub fn block_wallet(ctx: Context<BlockWallet>) -> Result<()> {
+        block_wallet::handler(ctx)
+    }
+
+    /// buys product tokens listed by a holder, the seller receives a royalty and the marketplace its fee
+    pub fn buy_resale<'info>(ctx: Context<'_, '_, '_, 'info, BuyResale<'info>>, amount: u64, payment_hook_accounts: u8) -> Result<()> {
+        buy_resale::handler(ctx, amount, payment_hook_accounts)
+    }
+
+    /// reseller recovers the product tokens that have not been sold and closes the listing
+    pub fn cancel_resale<'info>(ctx: Context<'_, '_, '_, 'info, CancelResale<'info>>) -> Result<()> {
+        cancel_resale::handler(ctx)
+    }
+
+    /// marketplace authority ends a campaign before its end time, the bounty vaults can be withdrawn once every campaign is closed
+    pub fn close_campaign(ctx: Context<CloseCampaign>) -> Result<()> {
+        close_campaign::handler(ctx)
+    }
+
+    /// marketplace authority rejects an access request, the requester gets the rent back and the reason is recorded
+    pub fn deny_access(ctx: Context<DenyAccess>, reason: u16) -> Result<()> {
+        deny_access::handler(ctx, reason)
+    }
+
+    /// marketplace authority changes the delegate of the blocklist
+    pub fn edit_blocklist(ctx: Context<EditBlocklist>, delegate: Pubkey) -> Result<()> {
+        edit_blocklist::handler(ctx, delegate)
     }
 
-    /// seller can edit payment_mint and product_price
-    pub fn edit_product(ctx: Context<EditProduct>, product_price: u64) -> Result<()> {
-        edit_product::handler(ctx, product_price)
+    /// seller can edit payment_mint, product_price and the gate of the buyers, None removes the gate
+    pub fn edit_product(ctx: Context<EditProduct>, product_price: u64, buyer_gate: Option<AccessGate>) -> Result<()> {
+        edit_product::handler(ctx, product_price, buyer_gate)
     }
 
-    /// marketplace authority can edit fees and rewards configs
+    /// marketplace authority can edit fees and permission configs
     pub fn edit_marketplace(ctx: Context<EditMarketplace>, params: EditMarketplaceParams) -> Result<()> {
         edit_marketplace::handler(ctx, params)
     }
 
+    /// anyone can close an access request once the expiry set by the marketplace has passed
+    pub fn expire_access(ctx: Context<ExpireAccess>) -> Result<()> {
+        expire_access::handler(ctx)
+    }
+
+    /// anyone can top up a bounty vault of the marketplace, the new balance is recorded in an event
+    pub fn fund_bounty<'info>(ctx: Context<'_, '_, '_, 'info, FundBounty<'info>>, amount: u64) -> Result<()> {
+        fund_bounty::handler(ctx, amount)
+    }
+
+    /// marketplace authority creates the compliance blocklist, the delegate can also block and unblock wallets
+    pub fn init_blocklist(ctx: Context<InitBlocklist>, delegate: Pubkey) -> Result<()> {
+        init_blocklist::handler(ctx, delegate)
+    }
+
     /// marketplace auth can create multiple bounty vaults (different mints)
     pub fn init_bounty(ctx: Context<InitBounty>) -> Result<()> {
         init_bounty::handler(ctx)
     }
 
+    /// marketplace authority creates a time-boxed reward campaign with its own budget and rewards
+    pub fn init_campaign(ctx: Context<InitCampaign>, params: InitCampaignParams) -> Result<()> {
+        init_campaign::handler(ctx, params)
+    }
+
+    /// program upgrade authority creates the program-wide pause switch and sets who can use it
+    pub fn init_guardian(ctx: Context<InitGuardian>, authority: Pubkey) -> Result<()> {
+        init_guardian::handler(ctx, authority)
+    }
+
     /// recommeded to read the Marketplace state code to understand the meaning of this data structure 
     pub fn init_marketplace(ctx: Context<InitMarketplace>, params: InitMarketplaceParams) -> Result<()> {
         init_marketplace::handler(ctx, params)
     }
 
+    /// marketplace authority creates the non-transferable loyalty points mint, campaigns can pay with it
+    pub fn init_points_mint(ctx: Context<InitPointsMint>, points_discount: u16) -> Result<()> {
+        init_points_mint::handler(ctx, points_discount)
+    }
+
     /// recommeded to read the Product state code to understand the meaning of this data structure 
     pub fn init_product_tree(ctx: Context<InitProductTree>, params: InitProductTreeParams) -> Result<()> {
         init_product_tree::handler(ctx, params)
@@ -53,7 +121,12 @@ pub mod marketplace_manager {
         init_product::handler(ctx, params)
     }
 
-    /// if a marketplace wants to change the reward mint, sellers and buyers have to create a new vault
+    /// sellers can give a bonus to the buyers of a product, paid from the promotion vault they fund
+    pub fn init_promotion(ctx: Context<InitPromotion>, params: InitPromotionParams) -> Result<()> {
+        init_promotion::handler(ctx, params)
+    }
+
+    /// campaigns can reward different mints, sellers and buyers need a vault for each of them
     /// because there is only one PDA, reward is the authority of these vaults
     pub fn init_reward_vault(ctx: Context<InitRewardVault>) -> Result<()> {
         init_reward_vault::handler(ctx)
@@ -62,26 +135,102 @@ pub mod marketplace_manager {
     pub fn init_reward(ctx: Context<InitReward>) -> Result<()> {
         init_reward::handler(ctx)
     }
+
+    /// sellers and buyers track the vesting of a reward vault, required when the marketplace vests rewards
+    pub fn init_reward_ledger(ctx: Context<InitRewardLedger>) -> Result<()> {
+        init_reward_ledger::handler(ctx)
+    }
+
+    /// product token holders can put them on sale, only if the marketplace makes them transferable
+    pub fn list_resale<'info>(ctx: Context<'_, '_, '_, 'info, ListResale<'info>>, params: ListResaleParams) -> Result<()> {
+        list_resale::handler(ctx, params)
+    }
+
+    /// requester moves an access request made with the first layout to the current one, paying the extra rent
+    pub fn migrate_access(ctx: Context<MigrateAccess>) -> Result<()> {
+        migrate_access::handler(ctx)
+    }
+
+    /// marketplace authority moves a marketplace made with the first layout to the current one, paying the extra rent
+    /// its rewards, if enabled, become a campaign that ends at campaign_end
+    pub fn migrate_marketplace(ctx: Context<MigrateMarketplace>, campaign_end: i64) -> Result<()> {
+        migrate_marketplace::handler(ctx, campaign_end)
+    }
+
+    /// seller moves a product made with the first layout to the current one and sets its marketplace
+    pub fn migrate_product(ctx: Context<MigrateProduct>) -> Result<()> {
+        migrate_product::handler(ctx)
+    }
+
+    /// reward authority moves a reward account made with the first layout to the current one
+    pub fn migrate_reward(ctx: Context<MigrateReward>) -> Result<()> {
+        migrate_reward::handler(ctx)
+    }
     
-    pub fn register_buy_cnft(ctx: Context<RegisterBuyCnft>, params: RegisterBuyCnftParams) -> Result<()> {
+    /// marketplace authority halts or resumes purchases, resales, product creation, rewards, points and referrals in its marketplace
+    pub fn pause_marketplace(ctx: Context<PauseMarketplace>, paused: bool) -> Result<()> {
+        pause_marketplace::handler(ctx, paused)
+    }
+
+    /// buyer burns product tokens to claim what they represent, the seller fulfills it off-chain
+    pub fn redeem(ctx: Context<Redeem>, params: RedeemParams) -> Result<()> {
+        redeem::handler(ctx, params)
+    }
+
+    pub fn register_buy_cnft<'info>(ctx: Context<'_, '_, '_, 'info, RegisterBuyCnft<'info>>, params: RegisterBuyCnftParams) -> Result<()> {
         register_buy_cnft::handler(ctx, params)
     }
 
     /// manages the transfers (buyer -> seller and fees to marketplace authority) 
     /// and buyers receive a token as a proof of payment (each product has its own tokenc)
-    pub fn register_buy_fungible(ctx: Context<RegisterBuyToken>, amount: u32) -> Result<()> {
-        register_buy_fungible::handler(ctx, amount)
+    pub fn register_buy_fungible<'info>(ctx: Context<'_, '_, '_, 'info, RegisterBuyToken<'info>>, amount: u32, proof: Vec<[u8; 32]>, payment_hook_accounts: u8) -> Result<()> {
+        register_buy_fungible::handler(ctx, amount, proof, payment_hook_accounts)
     }
 
     /// manages the transfers (buyer -> seller and fees to marketplace authority)
     /// uses payment pda to index transactions, but it does not initilize it
-    pub fn register_buy(ctx: Context<RegisterBuy>, amount: u32) -> Result<()> {
-        register_buy::handler(ctx, amount)
+    pub fn register_buy<'info>(ctx: Context<'_, '_, '_, 'info, RegisterBuy<'info>>, amount: u32, proof: Vec<[u8; 32]>, payment_hook_accounts: u8) -> Result<()> {
+        register_buy::handler(ctx, amount, proof, payment_hook_accounts)
+    }
+
+    /// buyers register the wallet that referred them once, the referrer gets a share of their rewards for a period
+    pub fn register_referral(ctx: Context<RegisterReferral>) -> Result<()> {
+        register_referral::handler(ctx)
     }
 
     /// creates on chain request to get access to sell products in a specific marketplace
-    pub fn request_access(ctx: Context<RequestAccess>) -> Result<()> {
-        request_access::handler(ctx)
+    pub fn request_access(ctx: Context<RequestAccess>, params: RequestAccessParams) -> Result<()> {
+        request_access::handler(ctx, params)
+    }
+
+    /// marketplace authority stops accepting a payment mint, products paid with it can not be sold until they are edited
+    pub fn remove_payment_mint(ctx: Context<RemovePaymentMint>) -> Result<()> {
+        remove_payment_mint::handler(ctx)
+    }
+
+    /// marketplace authority extends the access of a seller, from the current expiry or from now if it already expired
+    pub fn renew_access(ctx: Context<RenewAccess>, duration: i64) -> Result<()> {
+        renew_access::handler(ctx, duration)
+    }
+
+    /// marketplace authority takes the access back from a seller, revoking its access record and burning or freezing the access token
+    pub fn revoke_access(ctx: Context<RevokeAccess>, mode: RevokeMode) -> Result<()> {
+        revoke_access::handler(ctx, mode)
+    }
+
+    /// guardian authority halts or resumes every marketplace
+    pub fn set_guardian_pause(ctx: Context<SetGuardianPause>, paused: bool) -> Result<()> {
+        set_guardian_pause::handler(ctx, paused)
+    }
+
+    /// buyers burn loyalty points to get a discount on their next purchase in the marketplace
+    pub fn spend_points(ctx: Context<SpendPoints>, points: u64) -> Result<()> {
+        spend_points::handler(ctx, points)
+    }
+
+    /// marketplace authority or the blocklist delegate unblock a wallet, closing its blocklist entry
+    pub fn unblock_wallet(ctx: Context<UnblockWallet>) -> Result<()> {
+        unblock_wallet::handler(ctx)
     }
 
     /// creates o new tree related to the product
@@ -89,8 +238,19 @@ pub mod marketplace_manager {
         update_tree::handler(ctx, params)
     }
     
-    /// when promotion is ended users can withdraw the funds stored in the vaults, managed by the reward PFA
-    pub fn withdraw_reward(ctx: Context<WithdrawReward>) -> Result<()> {
+    /// marketplace auth recovers the unused budget of a bounty vault once every campaign has been closed
+    pub fn withdraw_bounty<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawBounty<'info>>, amount: u64) -> Result<()> {
+        withdraw_bounty::handler(ctx, amount)
+    }
+
+    /// sellers recover the tokens left in the promotion vault and close the promotion once it has ended
+    pub fn withdraw_promotion<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawPromotion<'info>>) -> Result<()> {
+        withdraw_promotion::handler(ctx)
+    }
+
+    /// when the campaigns that credited the reward have ended users can withdraw the funds stored in the vaults, managed by the reward PDA
+    /// if the marketplace vests the rewards, the vested portion can be withdrawn at any time
+    pub fn withdraw_reward<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawReward<'info>>) -> Result<()> {
         withdraw_reward::handler(ctx)
     }
 }
@@ -14,6 +14,8 @@ pub struct Marketplace {
     pub fees_config: FeesConfig,
     /// Set of rewards configuration that can be modified by the authority.
     pub rewards_config: RewardsConfig,
+    /// When true, purchases, product creation and reward transfers are halted, withdrawals are still allowed.
+    pub paused: bool,
     /// Seed bump parameters used for deterministic address derivation.
     pub bumps: MarketplaceBumps,
 }
@@ -30,8 +32,30 @@ pub struct PermissionConfig {
     pub access_mint: Pubkey,
     /// True = permissionless marketplace, false = only wallets with a specific token can create products.
     pub permissionless: bool,
+    /// Seconds after which an access request that has not been answered can be closed by anyone.
+    /// A value of 0 indicates that requests never expire.
+    pub request_expiry: i64,
+    /// What sellers need to hold to create products when the marketplace is not permissionless.
+    pub access_gate: AccessGate,
+    /// True when the marketplace has a blocklist, then it has to be provided to create products and buy.
+    pub blocklist_enabled: bool,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Default, Clone, PartialEq)]
+pub enum AccessGate {
+    /// The access token minted by the marketplace, granted with airdrop_access or accept_access.
+    #[default]
+    AccessMint,
+    /// At least `min_amount` of an external token.
+    Token { mint: Pubkey, min_amount: u64 },
+    /// An NFT that belongs to a verified Metaplex collection.
+    Collection { collection: Pubkey },
+    /// Merkle root of the approved seller wallets, sellers provide a proof instead of holding a token.
+    Allowlist { root: [u8; 32] },
+}
+
+pub const ACCESS_GATE_SIZE: usize = 1 + 32 + 8;
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct FeesConfig {
     /// The transaction fee percentage levied by the app or marketplace.
@@ -43,6 +67,8 @@ pub struct FeesConfig {
     pub discount_mint: Pubkey,
     /// Fee reduction percentage applied if the seller chooses to receive a specific token as payment.
     pub fee_reduction: u16,
+    /// If true, products can only be paid with the mints accepted by the marketplace (PaymentMintConfig accounts).
+    pub restricted_payment_mints: bool,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -53,18 +79,27 @@ pub enum PaymentFeePayer {
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct RewardsConfig {
-    /// This flag enables or disables the reward system.
-    /// When false, the reward system is inactive regardless of the reward_mint value.
-    pub rewards_enabled: bool,
-    /// If set, the marketplace will only give rewards if the payment is made with this specific mint.
-    /// To enable rewards irrespective of payment mint, set this value to default pubkey.
-    pub reward_mint: Pubkey,
-    /// The transaction volume percentage that the seller receives as a reward on a sale.
-    /// A value of 250 corresponds to a reward of 2.5% of the transaction volume.
-    /// A value of 0 indicates that there is no active rewards for the seller.
-    pub seller_reward: u16,
-    /// The transaction volume percentage that the buyer receives as a reward on a sale.
-    pub buyer_reward: u16,
+    /// Number of reward campaigns that have not been closed, the bounty vaults can only be withdrawn when there are none.
+    /// Rewards are given through RewardCampaign accounts, several of them can be active at the same time.
+    pub open_campaigns: u32,
+    /// Seconds after a credit before the rewards start to vest, the cliff amount is released at once.
+    pub vesting_cliff: i64,
+    /// Seconds during which the rewards vest linearly, a value of 0 indicates that there is no vesting
+    /// and rewards can be withdrawn once the campaigns that credited them have ended.
+    pub vesting_duration: i64,
+    /// NonTransferable token-2022 mint of the marketplace loyalty points, default pubkey when it has not been created.
+    /// Campaigns that pay in this mint mint the points instead of transferring them from a bounty vault.
+    pub points_mint: Pubkey,
+    /// Discount in basis points given for each point spent, applied to the next purchase of the buyer.
+    /// The marketplace funds it with its fee, so the discount can not be higher than the fee.
+    pub points_discount: u16,
+    /// When true the buyer bonus is transferred to the buyer token account in the purchase,
+    /// buyers do not need a Reward account and the bonus is not vested.
+    pub instant_cashback: bool,
+    /// Percentage of the buyer rewards credited to the wallet that referred the buyer, in basis points.
+    pub referral_reward: u16,
+    /// Seconds after the registration of a referral during which the referrer is credited.
+    pub referral_duration: i64,
 }
 
 /// Bump seed parameters used for deterministic address derivation.
@@ -72,6 +107,8 @@ pub struct RewardsConfig {
 pub struct MarketplaceBumps {
     pub bump: u8,
     pub access_mint_bump: u8,
+    /// Bump of the "null" PDA that campaigns use to reward any payment mint.
+    pub null_bump: u8,
 }
 
 pub const MARKETPLACE_SIZE: usize = 8  // discriminator
@@ -81,19 +118,62 @@ pub const MARKETPLACE_SIZE: usize = 8  // discriminator
     // PermissionConfig
     + 32  // access_mint
     + 1   // permissionless
+    + 8   // request_expiry
+    + ACCESS_GATE_SIZE // access_gate
+    + 1   // blocklist_enabled
     // FeesConfig
     + 2   // fee
     + 1   // fee_payer
     + 32  // discount_mint
     + 2   // fee_reduction
+    + 1   // restricted_payment_mints
     // RewardsConfig
-    + 1   // rewards_enabled
-    + 32  // reward_mint
-    + 2   // seller_reward
-    + 2   // buyer_reward
+    + 4   // open_campaigns
+    + 8   // vesting_cliff
+    + 8   // vesting_duration
+    + 32  // points_mint
+    + 2   // points_discount
+    + 1   // instant_cashback
+    + 2   // referral_reward
+    + 8   // referral_duration
+    + 1   // paused
     // MarketplaceBumps
     + 1   // bump
-    + 1;  // access_mint_bump
+    + 1   // access_mint_bump
+    + 1;  // null_bump
+
+/// Payment mint accepted by a marketplace, with its own settings.
+#[account]
+pub struct PaymentMintConfig {
+    pub marketplace: Pubkey,
+    pub mint: Pubkey,
+    /// Decimals of the mint when it was accepted, products are rejected if they do not match.
+    pub decimals: u8,
+    /// Marketplace fee applied to the payments made with this mint instead of the default one.
+    pub fee_override: Option<u16>,
+    pub bump: u8,
+}
+
+pub const PAYMENT_MINT_CONFIG_SIZE: usize = 8 // discriminator
+    + 32  // marketplace
+    + 32  // mint
+    + 1   // decimals
+    + 1 + 2 // fee_override
+    + 1;  // bump
+
+/// Program-wide emergency switch, initialized by the program upgrade authority.
+/// When paused every marketplace is halted as if its own paused flag was set.
+#[account]
+pub struct Guardian {
+    pub authority: Pubkey,
+    pub paused: bool,
+    pub bump: u8,
+}
+
+pub const GUARDIAN_SIZE: usize = 8 // discriminator
+    + 32  // authority
+    + 1   // paused
+    + 1;  // bump
 
 /// This account works as an product administrator
 #[account]
@@ -110,8 +190,16 @@ pub struct Product {
     pub merkle_tree: Pubkey,
     /// Seller-defined product configurations.
     pub seller_config: SellerConfig,
+    /// Wallets that share the royalties of the collection and the compressed NFTs minted on each sale.
+    /// Defaults to the seller, the compressed NFTs are only minted by products with a merkle tree.
+    pub creators: Vec<ProductCreator>,
+    /// If set, buyers need to hold a token, an NFT of a collection or be in an allowlist to buy this product.
+    /// The AccessMint gate is not allowed, the access token of the marketplace only gates sellers.
+    pub buyer_gate: Option<AccessGate>,
     /// Seed bump parameters used for deterministic address derivation.
     pub bumps: ProductBumps,
+    /// The marketplace where the product is sold, its fees and configs apply to sales and resales.
+    pub marketplace: Pubkey,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -120,8 +208,21 @@ pub struct SellerConfig {
     pub payment_mint: Pubkey,
     /// The product price in terms of payment token/mint.
     pub product_price: u64,
+    /// The percentage of every resale that goes to the seller.
+    /// For example, a value of 250 corresponds to a royalty of 2.5%.
+    pub royalty: u16,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProductCreator {
+    pub address: Pubkey,
+    /// Percentage of the royalties received by this creator, all the shares must add up to 100.
+    pub share: u8,
+}
+
+/// Same limit that Metaplex applies to the creators array.
+pub const MAX_CREATORS: usize = 5;
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct ProductBumps {
     pub bump: u8,
@@ -136,28 +237,406 @@ pub const PRODUCT_SIZE: usize = 8 // discriminator
     // SellerConfig
     + 32 // payment_mint
     + 8  // product_price
+    + 2  // royalty
+    + 4 + MAX_CREATORS * (32 + 1) // creators
+    + 1 + ACCESS_GATE_SIZE // buyer_gate
     // ProductBumps
     + 1  // product_bump
-    + 1; // mint_bump
+    + 1  // mint_bump
+    + 32; // marketplace
+
+/// This account represents product tokens that a holder puts on sale in the secondary market.
+/// The listed tokens are held by a vault controlled by this account until they are bought or the listing is cancelled.
+#[account]
+pub struct Listing {
+    /// The holder that is reselling the product tokens.
+    pub authority: Pubkey,
+    pub product: Pubkey,
+    /// Mint in which the reseller wants to be paid, it is the product payment mint when the listing is created.
+    pub payment_mint: Pubkey,
+    /// The price of each product token in terms of payment token/mint.
+    pub unit_price: u64,
+    /// Product tokens that are still available in this listing.
+    pub amount: u64,
+    /// Seed bump parameters used for deterministic address derivation.
+    pub bumps: ListingBumps,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ListingBumps {
+    pub bump: u8,
+    pub vault_bump: u8,
+}
+
+pub const LISTING_SIZE: usize = 8 // discriminator
+    + 32 // authority
+    + 32 // product
+    + 32 // payment_mint
+    + 8  // unit_price
+    + 8  // amount
+    // ListingBumps
+    + 1  // bump
+    + 1; // vault_bump
+
+/// This account is the on-chain proof that a buyer burned product tokens to claim the underlying good or service,
+/// the seller fulfills the order off-chain using this data.
+#[account]
+pub struct Redemption {
+    /// The buyer that burned the product tokens.
+    pub authority: Pubkey,
+    pub product: Pubkey,
+    /// Identifier chosen by the buyer, used as seed so the same wallet can redeem the same product several times.
+    pub id: [u8; 16],
+    /// Product tokens burned.
+    pub amount: u64,
+    /// Unix timestamp of the redemption.
+    pub timestamp: i64,
+    /// Hash of off-chain data needed to fulfill the order (eg: booking details).
+    pub reference: Option<[u8; 32]>,
+    pub bump: u8,
+}
+
+pub const REDEMPTION_SIZE: usize = 8 // discriminator
+    + 32 // authority
+    + 32 // product
+    + 16 // id
+    + 8  // amount
+    + 8  // timestamp
+    + 1 + 32 // reference
+    + 1; // bump
 
 #[account]
 pub struct Reward {
     pub authority: Pubkey,
+    /// End of the latest campaign that credited this account, the vaults can be withdrawn after it.
+    pub withdrawable_at: i64,
     pub bump: u8,
 }
 
 pub const REWARD_SIZE: usize = 8 // discriminator
     + 32  // authority
+    + 8   // withdrawable_at
     + 1;  // bump
 
+/// Tracks the vesting of the rewards credited to a reward vault.
+#[account]
+pub struct RewardLedger {
+    pub reward: Pubkey,
+    pub vault: Pubkey,
+    /// Rewards included in the current schedule, withdrawals do not change it.
+    pub locked: u64,
+    /// Start of the schedule, each credit moves it to the average of the credits weighted by their amount.
+    pub vesting_start: i64,
+    pub bump: u8,
+    /// Nothing vests before this timestamp, it moves with the start until the cliff of the marketplace has passed.
+    pub cliff_end: i64,
+    /// Vesting duration of the marketplace when the schedule started, a running schedule keeps it.
+    pub vesting_duration: i64,
+}
+
+pub const REWARD_LEDGER_SIZE: usize = 8 // discriminator
+    + 32  // reward
+    + 32  // vault
+    + 8   // locked
+    + 8   // vesting_start
+    + 1   // bump
+    + 8   // cliff_end
+    + 8;  // vesting_duration
+
+/// Discount obtained by burning loyalty points, consumed by the next purchase of the buyer in the marketplace.
+#[account]
+pub struct PointsDiscount {
+    pub authority: Pubkey,
+    pub marketplace: Pubkey,
+    /// Basis points of the price deducted from the marketplace fee of the purchase.
+    pub discount: u16,
+    pub bump: u8,
+}
+
+pub const POINTS_DISCOUNT_SIZE: usize = 8 // discriminator
+    + 32  // authority
+    + 32  // marketplace
+    + 2   // discount
+    + 1;  // bump
+
+/// Time-boxed reward program of a marketplace, funded from the bounty vaults.
+#[account]
+pub struct RewardCampaign {
+    pub marketplace: Pubkey,
+    pub id: [u8; 16],
+    /// Unix timestamps, purchases made between start and end are rewarded.
+    pub start: i64,
+    pub end: i64,
+    /// Maximum amount of tokens that the campaign can give.
+    pub budget: u64,
+    pub spent: u64,
+    /// Maximum reward that the seller or the buyer can receive in a single purchase, 0 means no limit.
+    pub max_reward: u64,
+    /// If set, rewards are only given if the payment is made with this specific mint.
+    /// To give rewards irrespective of payment mint, set this value to the "null" PDA.
+    pub reward_mint: Pubkey,
+    /// Mint in which the rewards are paid, funded from the bounty vault of that mint.
+    /// Default pubkey pays the rewards in the payment mint, budget and max_reward are measured in the payout mint.
+    pub payout_mint: Pubkey,
+    /// Payout tokens (base units) given per whole payment token spent, only used with a payout mint.
+    /// For example 10 LOYAL (6 decimals) per 1 USDC spent is 10000000.
+    pub payout_rate: u64,
+    /// The transaction volume percentage that the seller receives as a reward on a sale.
+    /// A value of 250 corresponds to a reward of 2.5% of the transaction volume.
+    pub seller_reward: u16,
+    /// The transaction volume percentage that the buyer receives as a reward on a sale.
+    pub buyer_reward: u16,
+    pub bump: u8,
+}
+
+pub const REWARD_CAMPAIGN_SIZE: usize = 8 // discriminator
+    + 32  // marketplace
+    + 16  // id
+    + 8   // start
+    + 8   // end
+    + 8   // budget
+    + 8   // spent
+    + 8   // max_reward
+    + 32  // reward_mint
+    + 32  // payout_mint
+    + 8   // payout_rate
+    + 2   // seller_reward
+    + 2   // buyer_reward
+    + 1;  // bump
+
+/// Promotion of a single product funded by its seller, paid on top of the marketplace rewards.
+#[account]
+pub struct Promotion {
+    pub product: Pubkey,
+    pub id: [u8; 16],
+    /// Seller that funds the promotion and can withdraw the remaining tokens once it ends, closing the promotion.
+    pub authority: Pubkey,
+    /// Token account owned by this PDA that holds the promotion tokens, in the payment mint of the product.
+    pub vault: Pubkey,
+    /// Unix timestamps, purchases made between start and end get the bonus.
+    pub start: i64,
+    pub end: i64,
+    /// The transaction volume percentage that the buyer receives as a bonus.
+    pub buyer_reward: u16,
+    pub bump: u8,
+}
+
+pub const PROMOTION_SIZE: usize = 8 // discriminator
+    + 32  // product
+    + 16  // id
+    + 32  // authority
+    + 32  // vault
+    + 8   // start
+    + 8   // end
+    + 2   // buyer_reward
+    + 1;  // bump
+
+/// Links a buyer with the wallet that referred it to a marketplace, it can only be registered once.
+#[account]
+pub struct Referral {
+    /// The referred buyer.
+    pub authority: Pubkey,
+    pub marketplace: Pubkey,
+    pub referrer: Pubkey,
+    /// Unix timestamp of the registration, the referrer is credited until it is older than the referral duration.
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+pub const REFERRAL_SIZE: usize = 8 // discriminator
+    + 32  // authority
+    + 32  // marketplace
+    + 32  // referrer
+    + 8   // created_at
+    + 1;  // bump
+
+/// This account is an application to sell in a token-gated marketplace, reviewed by the marketplace authority.
 #[account]
 pub struct Access {
     /// The user pubkey that request access to the marketplace.
     pub authority: Pubkey,
+    pub marketplace: Pubkey,
+    /// Off-chain application data (eg: seller profile or documentation).
+    pub uri: String,
+    /// Hash of the attestation issued by a KYC provider.
+    pub attestation: [u8; 32],
+    /// Free-form category defined by the marketplace (eg: "services").
+    pub category: String,
+    /// Unix timestamp of the request, used to know when it expires.
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+/// Compliance list of wallets that can not sell or buy in a marketplace,
+/// each blocked wallet has its own BlockedWallet account.
+#[account]
+pub struct Blocklist {
+    pub marketplace: Pubkey,
+    /// Wallet that can block and unblock wallets besides the marketplace authority.
+    pub delegate: Pubkey,
+    pub bump: u8,
+}
+
+pub const BLOCKLIST_SIZE: usize = 8 // discriminator
+    + 32  // marketplace
+    + 32  // delegate
+    + 1;  // bump
+
+/// Entry of the blocklist, the wallet is blocked while the account exists.
+#[account]
+pub struct BlockedWallet {
+    pub wallet: Pubkey,
+    pub marketplace: Pubkey,
     pub bump: u8,
 }
 
+pub const BLOCKED_WALLET_SIZE: usize = 8 // discriminator
+    + 32  // wallet
+    + 32  // marketplace
+    + 1;  // bump
+
+/// Access record of a seller in a token-gated marketplace, created when the access is granted.
+/// Used for paid memberships, once expired the seller can not create products or sell until it is renewed.
+#[account]
+pub struct SellerAccess {
+    pub authority: Pubkey,
+    pub marketplace: Pubkey,
+    /// Unix timestamp when the access ends, a value of 0 indicates that it never expires.
+    pub expires_at: i64,
+    pub bump: u8,
+    /// Set by the marketplace authority when it revokes the access, the seller can not sell until it is granted again.
+    pub revoked: bool,
+}
+
+pub const SELLER_ACCESS_SIZE: usize = 8 // discriminator
+    + 32  // authority
+    + 32  // marketplace
+    + 8   // expires_at
+    + 1   // bump
+    + 1;  // revoked
+
+/// Last rejection of the access requests of a wallet, kept after the request is closed so the requester can read it.
+#[account]
+pub struct AccessDenial {
+    pub authority: Pubkey,
+    pub marketplace: Pubkey,
+    /// Code defined by the marketplace to explain the rejection.
+    pub reason: u16,
+    pub denied_at: i64,
+    pub bump: u8,
+}
+
+pub const ACCESS_DENIAL_SIZE: usize = 8 // discriminator
+    + 32  // authority
+    + 32  // marketplace
+    + 2   // reason
+    + 8   // denied_at
+    + 1;  // bump
+
+/// Claim of the NFT a seller used to pass a collection gate, each NFT gives access to one seller of the marketplace.
+#[account]
+pub struct GateClaim {
+    pub seller: Pubkey,
+    pub mint: Pubkey,
+    pub bump: u8,
+}
+
+pub const GATE_CLAIM_SIZE: usize = 8 // discriminator
+    + 32  // seller
+    + 32  // mint
+    + 1;  // bump
+
+pub const MAX_URI_LENGTH: usize = 200;
+pub const MAX_CATEGORY_LENGTH: usize = 32;
+
 pub const ACCESS_SIZE: usize = 8 // discriminator
+    + 32  // authority
+    + 32  // marketplace
+    + 4 + MAX_URI_LENGTH // uri
+    + 32  // attestation
+    + 4 + MAX_CATEGORY_LENGTH // category
+    + 8   // created_at
+    + 1;  // bump
+
+/// Layout of the marketplaces created before the access gates, the campaigns and the pause were added.
+/// Rewards were configured in the marketplace, they are given by RewardCampaign accounts after the migration.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct MarketplaceV0 {
+    pub authority: Pubkey,
+    pub transferable: bool,
+    pub access_mint: Pubkey,
+    pub permissionless: bool,
+    pub fee: u16,
+    pub fee_payer: PaymentFeePayer,
+    pub discount_mint: Pubkey,
+    pub fee_reduction: u16,
+    pub rewards_enabled: bool,
+    pub reward_mint: Pubkey,
+    pub seller_reward: u16,
+    pub buyer_reward: u16,
+    pub bump: u8,
+    pub access_mint_bump: u8,
+}
+
+pub const MARKETPLACE_V0_SIZE: usize = 8  // discriminator
+    + 32  // authority
+    + 1   // transferable
+    + 32  // access_mint
+    + 1   // permissionless
+    + 2   // fee
+    + 1   // fee_payer
+    + 32  // discount_mint
+    + 2   // fee_reduction
+    + 1   // rewards_enabled
+    + 32  // reward_mint
+    + 2   // seller_reward
+    + 2   // buyer_reward
+    + 1   // bump
+    + 1;  // access_mint_bump
+
+/// Layout of the products created before royalties, creators, buyer gates and the marketplace were stored.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ProductV0 {
+    pub authority: Pubkey,
+    pub id: [u8; 16],
+    pub product_mint: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub payment_mint: Pubkey,
+    pub product_price: u64,
+    pub bump: u8,
+    pub mint_bump: u8,
+}
+
+pub const PRODUCT_V0_SIZE: usize = 8 // discriminator
+    + 32 // authority
+    + 16 // id
+    + 32 // product_mint
+    + 32 // merkle_tree
+    + 32 // payment_mint
+    + 8  // product_price
+    + 1  // product_bump
+    + 1; // mint_bump
+
+/// Layout of the reward accounts created before the withdrawal time was stored.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RewardV0 {
+    pub authority: Pubkey,
+    pub bump: u8,
+}
+
+pub const REWARD_V0_SIZE: usize = 8 // discriminator
+    + 32  // authority
+    + 1;  // bump
+
+/// Layout of the access requests created before the application data was stored.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct AccessV0 {
+    pub authority: Pubkey,
+    pub bump: u8,
+}
+
+/// The space of the marketplace was allocated but never written.
+pub const ACCESS_V0_SIZE: usize = 8 // discriminator
     + 32  // authority
     + 32  // marketplace
     + 1;  // bump
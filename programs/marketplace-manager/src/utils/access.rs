@@ -0,0 +1,340 @@
+use {
+    super::{assert_derivation, cmp_pubkeys, verify_merkle_proof},
+    crate::{error::ErrorCode, state::{AccessGate, GateClaim, PermissionConfig, SellerAccess, GATE_CLAIM_SIZE}},
+    anchor_lang::{
+        prelude::*,
+        solana_program::keccak::hash,
+        system_program::{allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer},
+    },
+    anchor_spl::{
+        token_2022::{mint_to, thaw_account, ThawAccount},
+        token_interface::{Mint, MintTo, TokenAccount},
+        metadata::{MetadataAccount, ID as TOKEN_METADATA_ID},
+    },
+};
+
+/// Sellers need a non-frozen access token to create products in a token-gated marketplace.
+pub fn assert_access(access_vault: &TokenAccount) -> Result<()> {
+    if access_vault.is_frozen() {
+        return Err(ErrorCode::AccessRevoked.into());
+    }
+
+    if access_vault.amount == 0 {
+        return Err(ErrorCode::NotInWithelist.into());
+    }
+
+    Ok(())
+}
+
+/// Validates that a user satisfies an access gate, used for sellers in the marketplace and buyers in restricted products.
+/// Token based gates need the user vault, collection gates also the mint and the metadata account of the NFT held in it
+/// and allowlists a proof of the user wallet.
+pub fn assert_access_gate(
+    access_gate: &AccessGate,
+    access_mint: &Pubkey,
+    user: &Pubkey,
+    access_vault: Option<&InterfaceAccount<TokenAccount>>,
+    gate_mint: Option<&InterfaceAccount<Mint>>,
+    gate_metadata: Option<&Account<MetadataAccount>>,
+    proof: &[[u8; 32]],
+) -> Result<()> {
+    if let AccessGate::Allowlist { root } = access_gate {
+        if !verify_merkle_proof(proof, *root, hash(user.as_ref()).0) {
+            return Err(ErrorCode::InvalidProof.into());
+        }
+
+        return Ok(());
+    }
+
+    let access_vault = access_vault.ok_or(ErrorCode::OptionalAccountNotProvided)?;
+    match access_gate {
+        AccessGate::AccessMint => {
+            if !cmp_pubkeys(&access_vault.mint, access_mint) {
+                return Err(ErrorCode::IncorrectMint.into());
+            }
+
+            assert_access(access_vault)
+        }
+        AccessGate::Token { mint, min_amount } => {
+            if !cmp_pubkeys(&access_vault.mint, mint) {
+                return Err(ErrorCode::IncorrectMint.into());
+            }
+
+            if access_vault.is_frozen() || access_vault.amount == 0 || access_vault.amount < *min_amount {
+                return Err(ErrorCode::NotInWithelist.into());
+            }
+
+            Ok(())
+        }
+        AccessGate::Collection { collection } => {
+            let gate_mint = gate_mint.ok_or(ErrorCode::OptionalAccountNotProvided)?;
+            if !cmp_pubkeys(&gate_mint.key(), &access_vault.mint) {
+                return Err(ErrorCode::IncorrectMint.into());
+            }
+            assert_gate_nft(gate_mint.supply, gate_mint.decimals)?;
+
+            let gate_metadata = gate_metadata.ok_or(ErrorCode::OptionalAccountNotProvided)?;
+            assert_derivation(
+                &TOKEN_METADATA_ID,
+                &gate_metadata.to_account_info(),
+                &[
+                    b"metadata",
+                    TOKEN_METADATA_ID.as_ref(),
+                    access_vault.mint.as_ref(),
+                ],
+            )?;
+
+            let is_verified_member = gate_metadata.collection.as_ref()
+                .map(|metadata_collection| metadata_collection.verified && cmp_pubkeys(&metadata_collection.key, collection))
+                .unwrap_or(false);
+            if !is_verified_member {
+                return Err(ErrorCode::IncorrectCollection.into());
+            }
+
+            if access_vault.is_frozen() || access_vault.amount == 0 {
+                return Err(ErrorCode::NotInWithelist.into());
+            }
+
+            Ok(())
+        }
+        AccessGate::Allowlist { .. } => Ok(()),
+    }
+}
+
+/// Collection gates need an NFT, otherwise the tokens of a single mint could give access to many wallets.
+pub fn assert_gate_nft(supply: u64, decimals: u8) -> std::result::Result<(), ErrorCode> {
+    if supply != 1 || decimals != 0 {
+        return Err(ErrorCode::IncorrectGateMint);
+    }
+
+    Ok(())
+}
+
+/// The access token of the marketplace gates sellers, so it can not be used to gate the buyers of a product.
+pub fn assert_buyer_gate(buyer_gate: &Option<AccessGate>) -> std::result::Result<(), ErrorCode> {
+    if let Some(AccessGate::AccessMint) = buyer_gate {
+        return Err(ErrorCode::IncorrectBuyerGate);
+    }
+
+    Ok(())
+}
+
+/// Each NFT of a collection gate gives access to one seller, the first seller that creates a product with it claims it.
+/// The claim is created in the ix logic because its seeds depend on the NFT held in the access vault.
+pub fn claim_gate_mint<'info>(
+    gate_claim: &AccountInfo<'info>,
+    marketplace: &Pubkey,
+    gate_mint: &Pubkey,
+    seller: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let (gate_claim_key, bump) = Pubkey::find_program_address(
+        &[b"gate_claim", marketplace.as_ref(), gate_mint.as_ref()],
+        &crate::ID,
+    );
+    if !cmp_pubkeys(&gate_claim_key, gate_claim.key) {
+        return Err(ErrorCode::IncorrectSeeds.into());
+    }
+
+    if cmp_pubkeys(gate_claim.owner, &crate::ID) && !gate_claim.data_is_empty() {
+        let gate_claim_data = gate_claim.try_borrow_data()?;
+        let claim = GateClaim::try_deserialize(&mut &gate_claim_data[..])?;
+        if !cmp_pubkeys(&claim.seller, seller.key) {
+            return Err(ErrorCode::GateClaimed.into());
+        }
+
+        return Ok(());
+    }
+
+    let gate_claim_seeds: &[&[u8]] = &[b"gate_claim", marketplace.as_ref(), gate_mint.as_ref(), &[bump]];
+    let lamports = Rent::get()?.minimum_balance(GATE_CLAIM_SIZE);
+    // lamports sent to the address in advance would make create_account fail
+    if gate_claim.lamports() == 0 {
+        create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                CreateAccount {
+                    from: seller.clone(),
+                    to: gate_claim.clone(),
+                },
+                &[gate_claim_seeds],
+            ),
+            lamports,
+            GATE_CLAIM_SIZE as u64,
+            &crate::ID,
+        ).map_err(|_| ErrorCode::CreateAccountError)?;
+    } else {
+        let missing_lamports = lamports.saturating_sub(gate_claim.lamports());
+        if missing_lamports > 0 {
+            transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    Transfer {
+                        from: seller.clone(),
+                        to: gate_claim.clone(),
+                    },
+                ),
+                missing_lamports,
+            )?;
+        }
+        allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                Allocate { account_to_allocate: gate_claim.clone() },
+                &[gate_claim_seeds],
+            ),
+            GATE_CLAIM_SIZE as u64,
+        )?;
+        assign(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                Assign { account_to_assign: gate_claim.clone() },
+                &[gate_claim_seeds],
+            ),
+            &crate::ID,
+        )?;
+    }
+
+    let claim = GateClaim {
+        seller: seller.key(),
+        mint: *gate_mint,
+        bump,
+    };
+    let mut gate_claim_data = gate_claim.try_borrow_mut_data()?;
+    claim.try_serialize(&mut &mut gate_claim_data[..])?;
+
+    Ok(())
+}
+
+/// Fails if the marketplace has a blocklist and the wallet is in it,
+/// the blocked wallet account is validated with seeds and the wallet is blocked while it exists.
+pub fn assert_not_blocked(
+    permission_config: &PermissionConfig,
+    blocked_wallet: &AccountInfo,
+) -> Result<()> {
+    if !permission_config.blocklist_enabled {
+        return Ok(());
+    }
+
+    if cmp_pubkeys(blocked_wallet.owner, &crate::ID) && !blocked_wallet.data_is_empty() {
+        return Err(ErrorCode::Blocked.into());
+    }
+
+    Ok(())
+}
+
+/// Sellers of token-gated marketplaces need an access record that has not expired nor been revoked.
+/// The record is only deserialized if it exists, sellers that got access before the records existed never expire.
+pub fn assert_seller_access(seller_access: &AccountInfo) -> Result<()> {
+    if !cmp_pubkeys(seller_access.owner, &crate::ID) || seller_access.data_is_empty() {
+        return Ok(());
+    }
+
+    let seller_access_data = seller_access.try_borrow_data()?;
+    let seller_access = SellerAccess::try_deserialize(&mut &seller_access_data[..])?;
+    if seller_access.revoked {
+        return Err(ErrorCode::AccessRevoked.into());
+    }
+    if seller_access.expires_at != 0 && Clock::get()?.unix_timestamp >= seller_access.expires_at {
+        return Err(ErrorCode::AccessExpired.into());
+    }
+
+    Ok(())
+}
+
+/// A new expiry of a seller access has to be in the future, without one the current expiry is kept.
+pub fn assert_access_expiry(expires_at: Option<i64>, now: i64) -> std::result::Result<(), ErrorCode> {
+    match expires_at {
+        Some(expires_at) if expires_at <= now => Err(ErrorCode::IncorrectAccessExpiry),
+        _ => Ok(()),
+    }
+}
+
+/// Extends a seller access by the duration from its expiry, or from now if it has already expired.
+/// Records that never expire (expires_at 0) are kept as they are.
+pub fn calculate_renewed_expiry(expires_at: i64, now: i64, duration: i64) -> std::result::Result<i64, ErrorCode> {
+    if expires_at == 0 {
+        return Ok(0);
+    }
+
+    expires_at.max(now).checked_add(duration).ok_or(ErrorCode::NumericalOverflow)
+}
+
+/// Gives access to a seller, if the access was revoked the vault is thawed or a new token is minted.
+pub fn grant_access<'info>(
+    token_program: AccountInfo<'info>,
+    access_mint: AccountInfo<'info>,
+    access_vault: &TokenAccount,
+    access_vault_info: AccountInfo<'info>,
+    marketplace: AccountInfo<'info>,
+    marketplace_seeds: &[&[u8]],
+) -> Result<()> {
+    if access_vault.is_frozen() {
+        thaw_account(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                ThawAccount {
+                    account: access_vault_info.clone(),
+                    mint: access_mint.clone(),
+                    authority: marketplace.clone(),
+                },
+                &[marketplace_seeds],
+            ),
+        ).map_err(|_| ErrorCode::ThawAccountError)?;
+    }
+
+    if access_vault.amount == 0 {
+        mint_to(
+            CpiContext::new_with_signer(
+                token_program,
+                MintTo {
+                    mint: access_mint,
+                    to: access_vault_info,
+                    authority: marketplace,
+                },
+                &[marketplace_seeds],
+            ),
+            1
+        ).map_err(|_| ErrorCode::MintToError)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collection_gates_need_an_nft() {
+        assert!(assert_gate_nft(1, 0).is_ok());
+        assert!(matches!(assert_gate_nft(2, 0), Err(ErrorCode::IncorrectGateMint)));
+        assert!(matches!(assert_gate_nft(1, 6), Err(ErrorCode::IncorrectGateMint)));
+        assert!(matches!(assert_gate_nft(0, 0), Err(ErrorCode::IncorrectGateMint)));
+    }
+
+    #[test]
+    fn buyer_gates_can_not_use_the_access_mint() {
+        assert!(assert_buyer_gate(&None).is_ok());
+        assert!(assert_buyer_gate(&Some(AccessGate::Token { mint: Pubkey::new_unique(), min_amount: 1 })).is_ok());
+        assert!(assert_buyer_gate(&Some(AccessGate::Collection { collection: Pubkey::new_unique() })).is_ok());
+        assert!(assert_buyer_gate(&Some(AccessGate::Allowlist { root: [0; 32] })).is_ok());
+        assert!(matches!(assert_buyer_gate(&Some(AccessGate::AccessMint)), Err(ErrorCode::IncorrectBuyerGate)));
+    }
+
+    #[test]
+    fn access_expiries_must_be_in_the_future() {
+        assert!(assert_access_expiry(None, 100).is_ok());
+        assert!(assert_access_expiry(Some(101), 100).is_ok());
+        assert!(matches!(assert_access_expiry(Some(100), 100), Err(ErrorCode::IncorrectAccessExpiry)));
+        assert!(matches!(assert_access_expiry(Some(0), 100), Err(ErrorCode::IncorrectAccessExpiry)));
+    }
+
+    #[test]
+    fn renewals_keep_records_that_never_expire() {
+        assert_eq!(calculate_renewed_expiry(0, 100, 50).unwrap(), 0);
+        assert_eq!(calculate_renewed_expiry(200, 100, 50).unwrap(), 250);
+        // expired records are renewed from now
+        assert_eq!(calculate_renewed_expiry(80, 100, 50).unwrap(), 150);
+    }
+}
\ No newline at end of file
@@ -1,5 +1,5 @@
 use {
-    super::cmp_pubkeys,
+    super::{cmp_pubkeys, calculate_discount},
     crate::error::ErrorCode,
     crate::state::*,
     anchor_lang::{
@@ -9,7 +9,15 @@ use {
             Transfer as NativeTransfer
         },
     },    
-    anchor_spl::token::{transfer, Transfer},
+    spl_token_2022::{
+        extension::{
+            BaseStateWithExtensions,
+            StateWithExtensions,
+            transfer_fee::{TransferFee, TransferFeeConfig},
+        },
+        onchain::invoke_transfer_checked,
+        state::Mint as Mint2022,
+    },
 };
 
 pub fn handle_sol<'info>(
@@ -20,6 +28,7 @@ pub fn handle_sol<'info>(
     fees_config: FeesConfig,
     payment_mint: Pubkey,
     total_payment: u64,
+    discount: u16,
 ) -> Result<()> {
     if fees_config.fee > 0 {
         let (total_fee, seller_amount) = calculate_transfer_distribution(
@@ -27,6 +36,8 @@ pub fn handle_sol<'info>(
             payment_mint,
             total_payment,
         )?;
+        // the points discount of the buyer is funded by the marketplace, it is deducted from the fee
+        let total_fee = total_fee - calculate_discount(total_payment, discount, total_fee)?;
 
         native_transfer(
             CpiContext::new(
@@ -64,63 +75,346 @@ pub fn handle_sol<'info>(
     Ok(())
 }
 
+/// Pays the product with a token, the transfer fee of the payment mint is withheld from the price paid by the buyer.
+/// Returns the amount the payment is worth net of the transfer fee, the rewards are calculated from it.
 pub fn handle_spl<'info>(
     token_program: AccountInfo<'info>,
     signer: AccountInfo<'info>,
     marketplace_transfer_vault: AccountInfo<'info>,
     seller_transfer_vault: AccountInfo<'info>,
     buyer_transfer_vault: AccountInfo<'info>,
+    payment_mint: AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
     fees_config: FeesConfig,
-    payment_mint: Pubkey,
     total_payment: u64,
-) -> Result<()> {
+    discount: u16,
+) -> Result<u64> {
+    let transfer_fee = get_transfer_fee(&payment_mint)?;
+    let net_payment = calculate_net_amount(&transfer_fee, total_payment)?;
+
     if fees_config.fee > 0 {
-        let (total_fee, seller_amount) = calculate_transfer_distribution(
+        let fee_payer = fees_config.fee_payer.clone();
+        let (total_fee, _) = calculate_transfer_distribution(
             fees_config,
-            payment_mint,
+            payment_mint.key(),
+            net_payment,
+        )?;
+        // the points discount of the buyer is funded by the marketplace, it is deducted from the fee
+        let total_fee = total_fee - calculate_discount(net_payment, discount, total_fee)?;
+        let (marketplace_amount, _, seller_amount) = calculate_payment_transfers(
+            &transfer_fee,
+            &fee_payer,
+            total_fee,
+            0,
             total_payment,
         )?;
 
-        transfer(
-            CpiContext::new(
-                token_program.clone(), 
-                Transfer {
-                    from: buyer_transfer_vault.clone(),
-                    to: marketplace_transfer_vault,
-                    authority: signer.clone(),
-                },
-            ),
-            total_fee,
-        ).map_err(|_| ErrorCode::TransferError)?;
+        transfer_checked_with_hook(
+            token_program.clone(),
+            buyer_transfer_vault.clone(),
+            payment_mint.clone(),
+            marketplace_transfer_vault,
+            signer.clone(),
+            remaining_accounts,
+            marketplace_amount,
+            &[],
+        )?;
 
-        transfer(
-            CpiContext::new(
-                token_program, 
-                Transfer {
-                    from: buyer_transfer_vault,
-                    to: seller_transfer_vault,
-                    authority: signer,
-                },
-            ),
+        transfer_checked_with_hook(
+            token_program,
+            buyer_transfer_vault,
+            payment_mint,
+            seller_transfer_vault,
+            signer,
+            remaining_accounts,
             seller_amount,
-        ).map_err(|_| ErrorCode::TransferError)?;
+            &[],
+        )?;
     } else {
-        transfer(
-            CpiContext::new(
-                token_program, 
-                Transfer {
-                    from: buyer_transfer_vault,
-                    to: seller_transfer_vault,
-                    authority: signer,
-                },
-            ),
+        transfer_checked_with_hook(
+            token_program,
+            buyer_transfer_vault,
+            payment_mint,
+            seller_transfer_vault,
+            signer,
+            remaining_accounts,
             total_payment,
-        ).map_err(|_| ErrorCode::TransferError)?;
+            &[],
+        )?;
+    }
+
+    Ok(net_payment)
+}
+
+pub fn handle_resale_sol<'info>(
+    system_program: AccountInfo<'info>,
+    signer: AccountInfo<'info>,
+    marketplace_auth: AccountInfo<'info>,
+    seller: AccountInfo<'info>,
+    reseller: AccountInfo<'info>,
+    fees_config: FeesConfig,
+    royalty: u16,
+    payment_mint: Pubkey,
+    total_payment: u64,
+) -> Result<()> {
+    let (total_fee, royalty_amount, reseller_amount) = calculate_resale_distribution(
+        fees_config,
+        royalty,
+        payment_mint,
+        total_payment,
+    )?;
+
+    if total_fee > 0 {
+        native_transfer(
+            CpiContext::new(
+                system_program.clone(), 
+                NativeTransfer {
+                    from: signer.clone(),
+                    to: marketplace_auth,
+            }), 
+            total_fee
+        )?;
+    }
+
+    if royalty_amount > 0 {
+        native_transfer(
+            CpiContext::new(
+                system_program.clone(), 
+                NativeTransfer {
+                    from: signer.clone(),
+                    to: seller,
+            }), 
+            royalty_amount
+        )?;
+    }
+
+    native_transfer(
+        CpiContext::new(
+            system_program, 
+            NativeTransfer {
+                from: signer,
+                to: reseller,
+            }
+        ), 
+        reseller_amount
+    )?;
+
+    Ok(())
+}
+
+/// Pays a resale with a token, the marketplace fee and the royalty are calculated net of the transfer fee of the payment mint.
+pub fn handle_resale_spl<'info>(
+    token_program: AccountInfo<'info>,
+    signer: AccountInfo<'info>,
+    marketplace_transfer_vault: AccountInfo<'info>,
+    seller_transfer_vault: AccountInfo<'info>,
+    reseller_transfer_vault: AccountInfo<'info>,
+    buyer_transfer_vault: AccountInfo<'info>,
+    payment_mint: AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    fees_config: FeesConfig,
+    royalty: u16,
+    total_payment: u64,
+) -> Result<()> {
+    let transfer_fee = get_transfer_fee(&payment_mint)?;
+    let fee_payer = fees_config.fee_payer.clone();
+    let (total_fee, royalty_amount, _) = calculate_resale_distribution(
+        fees_config,
+        royalty,
+        payment_mint.key(),
+        calculate_net_amount(&transfer_fee, total_payment)?,
+    )?;
+    let (marketplace_amount, royalty_amount, reseller_amount) = calculate_payment_transfers(
+        &transfer_fee,
+        &fee_payer,
+        total_fee,
+        royalty_amount,
+        total_payment,
+    )?;
+
+    if marketplace_amount > 0 {
+        transfer_checked_with_hook(
+            token_program.clone(),
+            buyer_transfer_vault.clone(),
+            payment_mint.clone(),
+            marketplace_transfer_vault,
+            signer.clone(),
+            remaining_accounts,
+            marketplace_amount,
+            &[],
+        )?;
+    }
+
+    if royalty_amount > 0 {
+        transfer_checked_with_hook(
+            token_program.clone(),
+            buyer_transfer_vault.clone(),
+            payment_mint.clone(),
+            seller_transfer_vault,
+            signer.clone(),
+            remaining_accounts,
+            royalty_amount,
+            &[],
+        )?;
+    }
+
+    transfer_checked_with_hook(
+        token_program,
+        buyer_transfer_vault,
+        payment_mint,
+        reseller_transfer_vault,
+        signer,
+        remaining_accounts,
+        reseller_amount,
+        &[],
+    )?;
+
+    Ok(())
+}
+
+/// Deserializes the payment mint config when the mint has been added to the marketplace,
+/// the PDA is always required so the config (and its fee override) can't be omitted.
+pub fn load_payment_mint_config(payment_mint_config: &AccountInfo) -> Result<Option<PaymentMintConfig>> {
+    if !cmp_pubkeys(payment_mint_config.owner, &crate::ID) || payment_mint_config.data_is_empty() {
+        return Ok(None);
+    }
+
+    let payment_mint_config_data = payment_mint_config.try_borrow_data()?;
+    Ok(Some(PaymentMintConfig::try_deserialize(&mut &payment_mint_config_data[..])?))
+}
+
+/// When the marketplace restricts the payment mints, the mint needs to be accepted and keep its decimals.
+pub fn assert_payment_mint(
+    fees_config: &FeesConfig,
+    payment_mint_config: &Option<PaymentMintConfig>,
+    decimals: u8,
+) -> Result<()> {
+    if !fees_config.restricted_payment_mints {
+        return Ok(());
     }
 
+    let payment_mint_config = payment_mint_config.as_ref()
+        .ok_or(ErrorCode::PaymentMintNotAccepted)?;
+    if payment_mint_config.decimals != decimals {
+        return Err(ErrorCode::IncorrectDecimals.into());
+    }
+
+    Ok(())
+}
+
+/// Returns the fees config of the marketplace with the fee override of the payment mint, if any.
+pub fn get_fees_config(
+    fees_config: &FeesConfig,
+    payment_mint_config: &Option<PaymentMintConfig>,
+) -> FeesConfig {
+    let mut fees_config = fees_config.clone();
+    if let Some(fee_override) = payment_mint_config.as_ref().and_then(|config| config.fee_override) {
+        fees_config.fee = fee_override;
+    }
+
+    fees_config
+}
+
+/// Returns the transfer fee of the mint for the current epoch, mints without the TransferFee extension have no fee.
+pub fn get_transfer_fee(mint: &AccountInfo) -> Result<TransferFee> {
+    let mint_data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<Mint2022>::unpack(&mint_data)?;
+
+    Ok(match mint_state.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => *transfer_fee_config.get_epoch_fee(Clock::get()?.epoch),
+        Err(_) => TransferFee::default(),
+    })
+}
+
+/// Calculates the amount received once the transfer fee is withheld from the amount sent.
+pub fn calculate_net_amount(transfer_fee: &TransferFee, amount: u64) -> std::result::Result<u64, ErrorCode> {
+    transfer_fee.calculate_post_fee_amount(amount).ok_or(ErrorCode::NumericalOverflow)
+}
+
+/// Calculates the amount that has to be sent so `amount` is received once the transfer fee is withheld.
+pub fn calculate_gross_amount(transfer_fee: &TransferFee, amount: u64) -> std::result::Result<u64, ErrorCode> {
+    if amount == 0 {
+        return Ok(0);
+    }
+
+    match transfer_fee.calculate_pre_fee_amount(amount) {
+        Some(gross_amount) if gross_amount >= amount => Ok(gross_amount),
+        _ => Err(ErrorCode::NumericalOverflow),
+    }
+}
+
+/// Calculates the amounts sent to the marketplace, the seller (royalty) and the receiver of the payment.
+/// The fee and the royalty are grossed up so they are received in full, the receiver of the payment is sent
+/// the rest of the price, the transfer fee of its share is withheld from it.
+pub fn calculate_payment_transfers(
+    transfer_fee: &TransferFee,
+    fee_payer: &PaymentFeePayer,
+    total_fee: u64,
+    royalty_amount: u64,
+    total_payment: u64,
+) -> std::result::Result<(u64, u64, u64), ErrorCode> {
+    let marketplace_amount = calculate_gross_amount(transfer_fee, total_fee)?;
+    let royalty_amount = calculate_gross_amount(transfer_fee, royalty_amount)?;
+    // when the buyer pays the fee it is sent on top of the price
+    let deducted_fee = match fee_payer {
+        PaymentFeePayer::Buyer => 0,
+        PaymentFeePayer::Seller => marketplace_amount,
+    };
+
+    let seller_amount = total_payment
+        .checked_sub(deducted_fee)
+        .and_then(|amount| amount.checked_sub(royalty_amount))
+        .ok_or(ErrorCode::NumericalOverflow)?;
+
+    Ok((marketplace_amount, royalty_amount, seller_amount))
+}
+
+/// Transfers tokens with transfer_checked, valid for both token programs.
+/// Remaining accounts are forwarded, so the extra accounts of a transfer hook can be provided.
+/// The transfer fee of the mint is withheld from the amount, the payment amounts are grossed up beforehand.
+pub fn transfer_checked_with_hook<'info>(
+    token_program: AccountInfo<'info>,
+    from: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let decimals = {
+        let mint_data = mint.try_borrow_data()?;
+        StateWithExtensions::<Mint2022>::unpack(&mint_data)?.base.decimals
+    };
+
+    invoke_transfer_checked(
+        &token_program.key(),
+        from,
+        mint,
+        to,
+        authority,
+        remaining_accounts,
+        amount,
+        decimals,
+        signer_seeds,
+    ).map_err(|_| ErrorCode::TransferError)?;
+
     Ok(())
 }
 
+/// Splits the remaining accounts between the transfers of the payment mint and the rest of the instruction,
+/// the extra accounts of a transfer hook are only forwarded to the transfers of their own mint.
+pub fn split_hook_accounts<T>(
+    remaining_accounts: &[T],
+    payment_hook_accounts: u8,
+) -> std::result::Result<(&[T], &[T]), ErrorCode> {
+    if payment_hook_accounts as usize > remaining_accounts.len() {
+        return Err(ErrorCode::IncorrectHookAccounts);
+    }
+
+    Ok(remaining_accounts.split_at(payment_hook_accounts as usize))
+}
+
 /// Calculates the distribution of the token amount, considering transaction fee and potential fee reduction.
 /// Adjusts the fee if the payment mint is the same as the reward mint.
 /// Also is considered fee_payer decided by the marketplace.
@@ -147,4 +441,158 @@ pub fn calculate_transfer_distribution(
     };
 
     Ok((total_fee, seller_amount))
+}
+
+/// Resellers pay the royalty and, when the seller is the fee payer, also the marketplace fee,
+/// both together can not be more than the amount paid by the buyer.
+pub fn assert_resale_fees(
+    fees: &FeesConfig,
+    royalty: u16,
+) -> std::result::Result<(), ErrorCode> {
+    let reseller_fee = match fees.fee_payer {
+        PaymentFeePayer::Buyer => 0,
+        PaymentFeePayer::Seller => fees.fee,
+    };
+
+    if (royalty as u32) + (reseller_fee as u32) > 10000 {
+        return Err(ErrorCode::IncorrectRoyalty);
+    }
+
+    Ok(())
+}
+
+/// Calculates the distribution of a resale, the marketplace fee is calculated as in a primary sale
+/// and the seller royalty is always deducted from the amount that the reseller receives.
+pub fn calculate_resale_distribution(
+    fees: FeesConfig,
+    royalty: u16,
+    payment_mint: Pubkey, 
+    amount: u64,
+) -> std::result::Result<(u64, u64, u64), ErrorCode> {
+    let (total_fee, seller_amount) = calculate_transfer_distribution(
+        fees,
+        payment_mint,
+        amount,
+    )?;
+
+    let royalty_amount = (royalty as u128)
+        .checked_mul(amount as u128)
+        .ok_or(ErrorCode::NumericalOverflow)?
+        .checked_div(10000)
+        .ok_or(ErrorCode::NumericalOverflow)? as u64;
+
+    let reseller_amount = seller_amount.checked_sub(royalty_amount).ok_or(ErrorCode::NumericalOverflow)?;
+
+    Ok((total_fee, royalty_amount, reseller_amount))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hook_accounts_are_split_by_mint() {
+        let remaining_accounts = [1, 2, 3];
+
+        assert_eq!(split_hook_accounts(&remaining_accounts, 0).unwrap(), (&[][..], &[1, 2, 3][..]));
+        assert_eq!(split_hook_accounts(&remaining_accounts, 2).unwrap(), (&[1, 2][..], &[3][..]));
+        assert_eq!(split_hook_accounts(&remaining_accounts, 3).unwrap(), (&[1, 2, 3][..], &[][..]));
+    }
+
+    #[test]
+    fn hook_accounts_can_not_exceed_the_remaining_accounts() {
+        assert!(matches!(split_hook_accounts(&[1, 2], 3), Err(ErrorCode::IncorrectHookAccounts)));
+    }
+
+    fn fees_config(fee_payer: PaymentFeePayer) -> FeesConfig {
+        FeesConfig {
+            fee: 500,
+            fee_payer,
+            discount_mint: Pubkey::new_unique(),
+            fee_reduction: 0,
+            restricted_payment_mints: false,
+        }
+    }
+
+    #[test]
+    fn payments_are_distributed_net_of_the_transfer_fee() {
+        // 1% transfer fee
+        let transfer_fee = TransferFee {
+            epoch: 0.into(),
+            maximum_fee: 1_000_000.into(),
+            transfer_fee_basis_points: 100.into(),
+        };
+        let payment_mint = Pubkey::new_unique();
+
+        let net_payment = calculate_net_amount(&transfer_fee, 10_000).unwrap();
+        assert_eq!(net_payment, 9_900);
+        let (total_fee, seller_amount) = calculate_transfer_distribution(
+            fees_config(PaymentFeePayer::Seller),
+            payment_mint,
+            net_payment,
+        ).unwrap();
+        let (marketplace_amount, _, seller_transfer) = calculate_payment_transfers(
+            &transfer_fee,
+            &PaymentFeePayer::Seller,
+            total_fee,
+            0,
+            10_000,
+        ).unwrap();
+        // the buyer pays the list price and each receiver gets its share once the fee is withheld
+        assert_eq!(marketplace_amount + seller_transfer, 10_000);
+        assert_eq!(calculate_net_amount(&transfer_fee, marketplace_amount).unwrap(), total_fee);
+        assert_eq!(calculate_net_amount(&transfer_fee, seller_transfer).unwrap(), seller_amount);
+
+        let (total_fee, royalty_amount, reseller_amount) = calculate_resale_distribution(
+            fees_config(PaymentFeePayer::Seller),
+            1_000,
+            payment_mint,
+            net_payment,
+        ).unwrap();
+        let (marketplace_amount, royalty_transfer, reseller_transfer) = calculate_payment_transfers(
+            &transfer_fee,
+            &PaymentFeePayer::Seller,
+            total_fee,
+            royalty_amount,
+            10_000,
+        ).unwrap();
+        assert_eq!(marketplace_amount + royalty_transfer + reseller_transfer, 10_000);
+        assert_eq!(calculate_net_amount(&transfer_fee, marketplace_amount).unwrap(), total_fee);
+        assert_eq!(calculate_net_amount(&transfer_fee, royalty_transfer).unwrap(), royalty_amount);
+        assert_eq!(calculate_net_amount(&transfer_fee, reseller_transfer).unwrap(), reseller_amount);
+    }
+
+    #[test]
+    fn buyers_that_pay_the_fee_send_it_on_top_of_the_price() {
+        let transfer_fee = TransferFee {
+            epoch: 0.into(),
+            maximum_fee: 1_000_000.into(),
+            transfer_fee_basis_points: 100.into(),
+        };
+        let net_payment = calculate_net_amount(&transfer_fee, 10_000).unwrap();
+        let (total_fee, seller_amount) = calculate_transfer_distribution(
+            fees_config(PaymentFeePayer::Buyer),
+            Pubkey::new_unique(),
+            net_payment,
+        ).unwrap();
+        let (marketplace_amount, _, seller_transfer) = calculate_payment_transfers(
+            &transfer_fee,
+            &PaymentFeePayer::Buyer,
+            total_fee,
+            0,
+            10_000,
+        ).unwrap();
+
+        assert_eq!(seller_transfer, 10_000);
+        assert_eq!(calculate_net_amount(&transfer_fee, seller_transfer).unwrap(), seller_amount);
+        assert_eq!(calculate_net_amount(&transfer_fee, marketplace_amount).unwrap(), total_fee);
+    }
+
+    #[test]
+    fn mints_without_transfer_fee_are_not_grossed_up() {
+        let transfer_fee = TransferFee::default();
+
+        assert_eq!(calculate_net_amount(&transfer_fee, 10_000).unwrap(), 10_000);
+        assert_eq!(calculate_gross_amount(&transfer_fee, 10_000).unwrap(), 10_000);
+        assert_eq!(calculate_payment_transfers(&transfer_fee, &PaymentFeePayer::Seller, 500, 0, 10_000).unwrap(), (500, 0, 9_500));
+    }
 }
\ No newline at end of file
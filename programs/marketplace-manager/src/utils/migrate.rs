@@ -0,0 +1,263 @@
+use {
+    super::cmp_pubkeys,
+    crate::{error::ErrorCode, state::*},
+    anchor_lang::{
+        prelude::*,
+        system_program::{transfer, Transfer},
+    },
+};
+
+/// Reads an account written with a previous layout, it has to be owned by the program,
+/// start with the discriminator of its type and have the size of that layout.
+pub fn load_legacy_account<T: AnchorDeserialize>(
+    account: &AccountInfo,
+    discriminator: [u8; 8],
+    legacy_size: usize,
+) -> Result<T> {
+    if !cmp_pubkeys(account.owner, &crate::ID) {
+        return Err(ErrorCode::IncorrectAccountVersion.into());
+    }
+
+    let data = account.try_borrow_data()?;
+    if data.len() != legacy_size || data[..8] != discriminator {
+        return Err(ErrorCode::IncorrectAccountVersion.into());
+    }
+
+    T::deserialize(&mut &data[8..]).map_err(|_| ErrorCode::IncorrectAccountVersion.into())
+}
+
+/// Grows the account to the size of its current layout, the payer covers the extra rent, and writes the migrated data.
+pub fn migrate_account<'info, T: AccountSerialize>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    size: usize,
+    migrated: &T,
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(size).saturating_sub(account.lamports());
+    if rent > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            rent,
+        )?;
+    }
+
+    account.realloc(size, true)?;
+    let mut data = account.try_borrow_mut_data()?;
+    migrated.try_serialize(&mut &mut data[..])
+}
+
+/// Id of the campaign that keeps giving the rewards configured in a marketplace with the first layout.
+pub const LEGACY_CAMPAIGN_ID: [u8; 16] = [0; 16];
+
+/// Settings added after the first version keep the values a new marketplace gets, except the access gate
+/// that keeps the access mint. Enabled rewards are given by the campaign made with migrated_campaign.
+pub fn migrated_marketplace(legacy: MarketplaceV0, null_bump: u8) -> Marketplace {
+    Marketplace {
+        authority: legacy.authority,
+        token_config: TokenConfig {
+            transferable: legacy.transferable,
+        },
+        permission_config: PermissionConfig {
+            access_mint: legacy.access_mint,
+            permissionless: legacy.permissionless,
+            request_expiry: 0,
+            access_gate: AccessGate::AccessMint,
+            blocklist_enabled: false,
+        },
+        fees_config: FeesConfig {
+            fee: legacy.fee,
+            fee_payer: legacy.fee_payer,
+            discount_mint: legacy.discount_mint,
+            fee_reduction: legacy.fee_reduction,
+            restricted_payment_mints: false,
+        },
+        rewards_config: RewardsConfig {
+            open_campaigns: legacy.rewards_enabled as u32,
+            vesting_cliff: 0,
+            vesting_duration: 0,
+            points_mint: Pubkey::default(),
+            points_discount: 0,
+            instant_cashback: false,
+            referral_reward: 0,
+            referral_duration: 0,
+        },
+        paused: false,
+        bumps: MarketplaceBumps {
+            bump: legacy.bump,
+            access_mint_bump: legacy.access_mint_bump,
+            null_bump,
+        },
+    }
+}
+
+/// Rewards of a marketplace with the first layout, they were only limited by the tokens of the bounty vault
+/// so the campaign has no budget or reward limit.
+pub fn migrated_campaign(legacy: &MarketplaceV0, marketplace: Pubkey, start: i64, end: i64, bump: u8) -> RewardCampaign {
+    RewardCampaign {
+        marketplace,
+        id: LEGACY_CAMPAIGN_ID,
+        start,
+        end,
+        budget: u64::MAX,
+        spent: 0,
+        max_reward: 0,
+        reward_mint: legacy.reward_mint,
+        payout_mint: Pubkey::default(),
+        payout_rate: 0,
+        seller_reward: legacy.seller_reward,
+        buyer_reward: legacy.buyer_reward,
+        bump,
+    }
+}
+
+/// Products did not store their marketplace, the seller chooses it when the product is migrated.
+pub fn migrated_product(legacy: ProductV0, marketplace: Pubkey) -> Product {
+    Product {
+        authority: legacy.authority,
+        id: legacy.id,
+        product_mint: legacy.product_mint,
+        merkle_tree: legacy.merkle_tree,
+        seller_config: SellerConfig {
+            payment_mint: legacy.payment_mint,
+            product_price: legacy.product_price,
+            royalty: 0,
+        },
+        creators: Vec::from([ProductCreator {
+            address: legacy.authority,
+            share: 100,
+        }]),
+        buyer_gate: None,
+        bumps: ProductBumps {
+            bump: legacy.bump,
+            mint_bump: legacy.mint_bump,
+        },
+        marketplace,
+    }
+}
+
+pub fn migrated_reward(legacy: RewardV0) -> Reward {
+    Reward {
+        authority: legacy.authority,
+        withdrawable_at: 0,
+        bump: legacy.bump,
+    }
+}
+
+/// Requests did not store when they were made, the migration time is used so they do not expire at once.
+pub fn migrated_access(legacy: AccessV0, marketplace: Pubkey, now: i64) -> Access {
+    Access {
+        authority: legacy.authority,
+        marketplace,
+        uri: String::new(),
+        attestation: [0; 32],
+        category: String::new(),
+        created_at: now,
+        bump: legacy.bump,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serialized_size<T: AnchorSerialize>(data: &T) -> usize {
+        data.try_to_vec().unwrap().len()
+    }
+
+    #[test]
+    fn legacy_layouts_match_their_sizes() {
+        let marketplace = MarketplaceV0 {
+            authority: Pubkey::new_unique(),
+            transferable: true,
+            access_mint: Pubkey::new_unique(),
+            permissionless: false,
+            fee: 250,
+            fee_payer: PaymentFeePayer::Seller,
+            discount_mint: Pubkey::new_unique(),
+            fee_reduction: 100,
+            rewards_enabled: true,
+            reward_mint: Pubkey::new_unique(),
+            seller_reward: 20,
+            buyer_reward: 20,
+            bump: 255,
+            access_mint_bump: 254,
+        };
+        let product = ProductV0 {
+            authority: Pubkey::new_unique(),
+            id: [1; 16],
+            product_mint: Pubkey::new_unique(),
+            merkle_tree: Pubkey::default(),
+            payment_mint: Pubkey::new_unique(),
+            product_price: 100,
+            bump: 255,
+            mint_bump: 254,
+        };
+        assert_eq!(8 + serialized_size(&marketplace), MARKETPLACE_V0_SIZE);
+        assert_eq!(8 + serialized_size(&product), PRODUCT_V0_SIZE);
+        assert_eq!(8 + serialized_size(&RewardV0 { authority: Pubkey::new_unique(), bump: 255 }), REWARD_V0_SIZE);
+        // the marketplace of the request was allocated but not written
+        assert_eq!(8 + serialized_size(&AccessV0 { authority: Pubkey::new_unique(), bump: 255 }) + 32, ACCESS_V0_SIZE);
+    }
+
+    #[test]
+    fn migrated_accounts_keep_their_data_and_fit_the_current_sizes() {
+        let authority = Pubkey::new_unique();
+        let legacy_marketplace = MarketplaceV0 {
+            authority,
+            transferable: true,
+            access_mint: Pubkey::new_unique(),
+            permissionless: false,
+            fee: 250,
+            fee_payer: PaymentFeePayer::Seller,
+            discount_mint: Pubkey::new_unique(),
+            fee_reduction: 100,
+            rewards_enabled: true,
+            reward_mint: Pubkey::new_unique(),
+            seller_reward: 20,
+            buyer_reward: 20,
+            bump: 255,
+            access_mint_bump: 254,
+        };
+        let campaign = migrated_campaign(&legacy_marketplace, Pubkey::new_unique(), 100, 200, 252);
+        assert!(campaign.reward_mint == legacy_marketplace.reward_mint && campaign.id == LEGACY_CAMPAIGN_ID);
+        assert!(campaign.seller_reward == 20 && campaign.buyer_reward == 20);
+        assert!(campaign.start == 100 && campaign.end == 200 && campaign.budget == u64::MAX);
+        assert!(8 + serialized_size(&campaign) <= REWARD_CAMPAIGN_SIZE);
+
+        let marketplace = migrated_marketplace(legacy_marketplace, 253);
+        assert!(marketplace.authority == authority && marketplace.token_config.transferable);
+        assert_eq!(marketplace.rewards_config.open_campaigns, 1);
+        assert_eq!(marketplace.fees_config.fee, 250);
+        assert_eq!(marketplace.bumps.null_bump, 253);
+        assert!(8 + serialized_size(&marketplace) <= MARKETPLACE_SIZE);
+
+        let product = migrated_product(ProductV0 {
+            authority,
+            id: [1; 16],
+            product_mint: Pubkey::new_unique(),
+            merkle_tree: Pubkey::default(),
+            payment_mint: Pubkey::new_unique(),
+            product_price: 100,
+            bump: 255,
+            mint_bump: 254,
+        }, Pubkey::new_unique());
+        assert_eq!(product.seller_config.product_price, 100);
+        assert!(product.creators.len() == 1 && product.creators[0].address == authority);
+        assert!(8 + serialized_size(&product) <= PRODUCT_SIZE);
+
+        let reward = migrated_reward(RewardV0 { authority, bump: 255 });
+        assert_eq!(reward.withdrawable_at, 0);
+        assert!(8 + serialized_size(&reward) <= REWARD_SIZE);
+
+        let access = migrated_access(AccessV0 { authority, bump: 255 }, Pubkey::new_unique(), 100);
+        assert_eq!(access.created_at, 100);
+        assert!(8 + serialized_size(&access) <= ACCESS_SIZE);
+    }
+}
@@ -6,21 +6,137 @@ use {
         solana_program::program::invoke_signed,
     },
     anchor_spl::token_interface::{
-        InitializeMint, 
+        InitializeMint,
         initialize_mint,
     },
     spl_token_2022::{
-        extension::ExtensionType,
-        state::Mint as Mint2022,
-        instruction::initialize_non_transferable_mint,
-        
+        extension::{
+            ExtensionType,
+            metadata_pointer,
+            transfer_hook,
+            default_account_state::instruction::initialize_default_account_state,
+        },
+        state::{Mint as Mint2022, AccountState},
+        instruction::{
+            initialize_non_transferable_mint,
+            initialize_mint_close_authority,
+            initialize_permanent_delegate,
+        },
     },
+    spl_token_metadata_interface::instruction::initialize as initialize_token_metadata,
 };
 
+/// Token-2022 features that can be enabled when a mint is created.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum MintExtension {
+    NonTransferable,
+    /// Points to an external account that holds the token metadata.
+    MetadataPointer { metadata_address: Pubkey },
+    /// Metadata stored in the mint account itself, the metadata pointer is set to the mint.
+    TokenMetadata { name: String, symbol: String, uri: String },
+    /// The close authority and the permanent delegate have to be the mint authority.
+    MintCloseAuthority { close_authority: Pubkey },
+    PermanentDelegate { delegate: Pubkey },
+    /// The mint authority is the one allowed to update the hook program.
+    TransferHook { program_id: Pubkey },
+    /// New token accounts are frozen by default, requires a freeze authority.
+    DefaultAccountState { frozen: bool },
+}
+
+impl MintExtension {
+    pub fn extension_type(&self) -> ExtensionType {
+        match self {
+            MintExtension::NonTransferable => ExtensionType::NonTransferable,
+            MintExtension::MetadataPointer { .. } => ExtensionType::MetadataPointer,
+            MintExtension::TokenMetadata { .. } => ExtensionType::TokenMetadata,
+            MintExtension::MintCloseAuthority { .. } => ExtensionType::MintCloseAuthority,
+            MintExtension::PermanentDelegate { .. } => ExtensionType::PermanentDelegate,
+            MintExtension::TransferHook { .. } => ExtensionType::TransferHook,
+            MintExtension::DefaultAccountState { .. } => ExtensionType::DefaultAccountState,
+        }
+    }
+}
+
+/// TokenMetadata is a variable-length extension, token-2022 reallocs the mint when it is initialized
+/// so it is not included in the account space but its rent is paid upfront.
+fn token_metadata_len(name: &str, symbol: &str, uri: &str) -> usize {
+    2 + 2 // type + length
+    + 32 // update_authority
+    + 32 // mint
+    + 4 + name.len()
+    + 4 + symbol.len()
+    + 4 + uri.len()
+    + 4 // additional_metadata
+}
+
+/// Validates the extensions requested for a mint and returns the extension types to initialize.
+/// The permanent delegate and the close authority can only be the mint authority (the product PDA),
+/// otherwise the seller could move or destroy the tokens of the buyers.
+fn validate_extensions(
+    extensions: &[MintExtension],
+    mint_authority: &Pubkey,
+    freeze_authority: &Option<Pubkey>,
+) -> std::result::Result<Vec<ExtensionType>, ErrorCode> {
+    let mut extension_types: Vec<ExtensionType> = Vec::new();
+    for ext in extensions.iter() {
+        let extension_type = ext.extension_type();
+        if extension_types.contains(&extension_type) {
+            return Err(ErrorCode::IncorrectExtensions);
+        }
+        extension_types.push(extension_type);
+
+        match ext {
+            MintExtension::PermanentDelegate { delegate } if delegate != mint_authority => {
+                return Err(ErrorCode::IncorrectExtensions);
+            }
+            MintExtension::MintCloseAuthority { close_authority } if close_authority != mint_authority => {
+                return Err(ErrorCode::IncorrectExtensions);
+            }
+            _ => {}
+        }
+    }
+
+    // the metadata lives in the mint, so it needs a pointer to itself
+    if extension_types.contains(&ExtensionType::TokenMetadata) {
+        if extension_types.contains(&ExtensionType::MetadataPointer) {
+            return Err(ErrorCode::IncorrectExtensions);
+        }
+        extension_types.push(ExtensionType::MetadataPointer);
+    }
+
+    let is_frozen_by_default = extensions.contains(&MintExtension::DefaultAccountState { frozen: true });
+    if is_frozen_by_default && freeze_authority.is_none() {
+        return Err(ErrorCode::IncorrectExtensions);
+    }
+
+    Ok(extension_types)
+}
+
+/// Returns the space of the mint account and the space of the token metadata, which is not allocated
+/// when the account is created.
+fn mint_space(
+    extension_types: &[ExtensionType],
+    token_metadata: &Option<(String, String, String)>,
+) -> std::result::Result<(usize, usize), ErrorCode> {
+    let fixed_extension_types: Vec<ExtensionType> = extension_types
+        .iter()
+        .filter(|ext| **ext != ExtensionType::TokenMetadata)
+        .cloned()
+        .collect();
+    let space = ExtensionType::try_calculate_account_len::<Mint2022>(&fixed_extension_types)
+        .map_err(|_| ErrorCode::MintExtensionError)?;
+    let metadata_space = token_metadata.as_ref()
+        .map(|(name, symbol, uri)| token_metadata_len(name, symbol, uri))
+        .unwrap_or_default();
+
+    Ok((space, metadata_space))
+}
+
 pub fn mint_builder<'info>(
     mint_seeds: Vec<&[u8]>,
     mint_authority_seeds: Vec<&[u8]>,
-    extensions: Vec<ExtensionType>,
+    extensions: Vec<MintExtension>,
+    freeze_authority: Option<Pubkey>,
     system_program: AccountInfo<'info>,
     token_program: AccountInfo<'info>,
     rent_info: AccountInfo<'info>,
@@ -29,63 +145,210 @@ pub fn mint_builder<'info>(
     signer: AccountInfo<'info>,
     rent: Sysvar<'_, Rent>,
 ) -> std::result::Result<(), ErrorCode> {
-    let space = if extensions.is_empty() {
-        ExtensionType::try_calculate_account_len::<Mint2022>(&[]).unwrap_or_default()
-    } else {
-        extensions
-            .iter()
-            .map(|ext| ExtensionType::try_calculate_account_len::<Mint2022>(&[*ext]).unwrap_or_default())
-            .sum()
-    };
-    
+    let extension_types = validate_extensions(&extensions, &mint_authority.key(), &freeze_authority)?;
+    let token_metadata = extensions.iter().find_map(|ext| match ext {
+        MintExtension::TokenMetadata { name, symbol, uri } => Some((name.clone(), symbol.clone(), uri.clone())),
+        _ => None,
+    });
+    let (space, metadata_space) = mint_space(&extension_types, &token_metadata)?;
+
     create_account(
         CpiContext::new_with_signer(
             system_program,
-            CreateAccount { 
-                from: signer, 
+            CreateAccount {
+                from: signer,
                 to: mint.clone()
             },
             &[&mint_seeds[..]],
         ),
-        rent.minimum_balance(space), 
-        space as u64, 
+        rent.minimum_balance(space + metadata_space),
+        space as u64,
         &token_program.key()
     ).map_err(|_| ErrorCode::CreateAccountError)?;
 
-    for ext in extensions {
+    let mut extension_ixs = Vec::new();
+    if token_metadata.is_some() {
+        extension_ixs.push(metadata_pointer::instruction::initialize(
+            &token_program.key(),
+            &mint.key(),
+            Some(mint_authority.key()),
+            Some(mint.key()),
+        ));
+    }
+    for ext in extensions.iter() {
         match ext {
-            ExtensionType::NonTransferable => {
-                invoke_signed(
-                    &initialize_non_transferable_mint(
-                        &token_program.key(), 
-                        &mint.key().clone()
-                    ).map_err(|_| ErrorCode::TransferError)?,
-                    &[
-                        mint_authority.clone(),
-                        mint.clone()
-                    ],
-                    &[&mint_seeds[..]],
-                ).map_err(|_| ErrorCode::MintExtensionError)?;
+            MintExtension::NonTransferable => {
+                extension_ixs.push(initialize_non_transferable_mint(
+                    &token_program.key(),
+                    &mint.key(),
+                ));
+            }
+            MintExtension::MetadataPointer { metadata_address } => {
+                extension_ixs.push(metadata_pointer::instruction::initialize(
+                    &token_program.key(),
+                    &mint.key(),
+                    Some(mint_authority.key()),
+                    Some(*metadata_address),
+                ));
+            }
+            MintExtension::MintCloseAuthority { close_authority } => {
+                extension_ixs.push(initialize_mint_close_authority(
+                    &token_program.key(),
+                    &mint.key(),
+                    Some(close_authority),
+                ));
+            }
+            MintExtension::PermanentDelegate { delegate } => {
+                extension_ixs.push(initialize_permanent_delegate(
+                    &token_program.key(),
+                    &mint.key(),
+                    delegate,
+                ));
+            }
+            MintExtension::TransferHook { program_id } => {
+                extension_ixs.push(transfer_hook::instruction::initialize(
+                    &token_program.key(),
+                    &mint.key(),
+                    Some(mint_authority.key()),
+                    Some(*program_id),
+                ));
             }
-            _ => {
-                return Err(ErrorCode::ExtensionNotSupported.into());
+            MintExtension::DefaultAccountState { frozen } => {
+                let state = if *frozen { AccountState::Frozen } else { AccountState::Initialized };
+                extension_ixs.push(initialize_default_account_state(
+                    &token_program.key(),
+                    &mint.key(),
+                    &state,
+                ));
             }
+            // initialized after the mint
+            MintExtension::TokenMetadata { .. } => continue,
         }
-    }    
+    }
+
+    for ix in extension_ixs {
+        invoke_signed(
+            &ix.map_err(|_| ErrorCode::MintExtensionError)?,
+            &[
+                mint_authority.clone(),
+                mint.clone()
+            ],
+            &[&mint_seeds[..]],
+        ).map_err(|_| ErrorCode::MintExtensionError)?;
+    }
 
     initialize_mint(
         CpiContext::new_with_signer(
             token_program.to_account_info(),
             InitializeMint {
-                mint,
+                mint: mint.clone(),
                 rent: rent_info,
             },
             &[&mint_seeds[..], &mint_authority_seeds[..]],
         ),
-        0, 
-        &mint_authority.key(), 
-        None
+        0,
+        &mint_authority.key(),
+        freeze_authority.as_ref(),
     ).map_err(|_| ErrorCode::InitMintError)?;
 
+    if let Some((name, symbol, uri)) = token_metadata {
+        invoke_signed(
+            &initialize_token_metadata(
+                &token_program.key(),
+                &mint.key(),
+                &mint_authority.key(),
+                &mint.key(),
+                &mint_authority.key(),
+                name,
+                symbol,
+                uri,
+            ),
+            &[
+                mint.clone(),
+                mint_authority.clone(),
+            ],
+            &[&mint_authority_seeds[..]],
+        ).map_err(|_| ErrorCode::MintExtensionError)?;
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        anchor_lang::solana_program::program_pack::Pack,
+        spl_token_metadata_interface::state::TokenMetadata,
+    };
+
+    fn token_metadata() -> MintExtension {
+        MintExtension::TokenMetadata {
+            name: "Product".to_string(),
+            symbol: "PRD".to_string(),
+            uri: "https://example.com/product.json".to_string(),
+        }
+    }
+
+    #[test]
+    fn mint_space_includes_every_extension_and_the_metadata() {
+        let authority = Pubkey::new_unique();
+        let extensions = vec![
+            MintExtension::NonTransferable,
+            token_metadata(),
+            MintExtension::PermanentDelegate { delegate: authority },
+            MintExtension::TransferHook { program_id: Pubkey::new_unique() },
+        ];
+        let extension_types = validate_extensions(&extensions, &authority, &None).unwrap();
+        let token_metadata = Some(("Product".to_string(), "PRD".to_string(), "https://example.com/product.json".to_string()));
+        let (space, metadata_space) = mint_space(&extension_types, &token_metadata).unwrap();
+
+        let expected_space = ExtensionType::try_calculate_account_len::<Mint2022>(&[
+            ExtensionType::NonTransferable,
+            ExtensionType::PermanentDelegate,
+            ExtensionType::TransferHook,
+            ExtensionType::MetadataPointer,
+        ]).unwrap();
+        assert_eq!(space, expected_space);
+
+        // token-2022 stores the metadata with a 2 bytes type and a 2 bytes length
+        let metadata = TokenMetadata {
+            update_authority: Default::default(),
+            mint: Pubkey::new_unique(),
+            name: "Product".to_string(),
+            symbol: "PRD".to_string(),
+            uri: "https://example.com/product.json".to_string(),
+            additional_metadata: vec![],
+        };
+        assert_eq!(metadata_space, 4 + metadata.try_to_vec().unwrap().len());
+    }
+
+    #[test]
+    fn mint_space_without_extensions_is_a_plain_mint() {
+        let (space, metadata_space) = mint_space(&[], &None).unwrap();
+        assert_eq!(space, Mint2022::LEN);
+        assert_eq!(metadata_space, 0);
+    }
+
+    #[test]
+    fn invalid_extensions_are_rejected() {
+        let authority = Pubkey::new_unique();
+        let validate = |extensions: Vec<MintExtension>| validate_extensions(&extensions, &authority, &None);
+
+        assert!(validate(vec![MintExtension::NonTransferable, MintExtension::NonTransferable]).is_err());
+        assert!(validate(vec![token_metadata(), MintExtension::MetadataPointer { metadata_address: authority }]).is_err());
+        assert!(validate(vec![MintExtension::DefaultAccountState { frozen: true }]).is_err());
+        assert!(validate_extensions(&[MintExtension::DefaultAccountState { frozen: true }], &authority, &Some(authority)).is_ok());
+    }
+
+    #[test]
+    fn delegate_and_close_authority_must_be_the_mint_authority() {
+        let authority = Pubkey::new_unique();
+        let seller = Pubkey::new_unique();
+
+        assert!(validate_extensions(&[MintExtension::PermanentDelegate { delegate: seller }], &authority, &None).is_err());
+        assert!(validate_extensions(&[MintExtension::MintCloseAuthority { close_authority: seller }], &authority, &None).is_err());
+        assert!(validate_extensions(&[MintExtension::PermanentDelegate { delegate: authority }], &authority, &None).is_ok());
+        assert!(validate_extensions(&[MintExtension::MintCloseAuthority { close_authority: authority }], &authority, &None).is_ok());
+    }
+}
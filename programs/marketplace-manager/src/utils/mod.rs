@@ -1,31 +1,76 @@
+pub mod access;
 pub mod mint_builder;
 pub mod handle_payment;
+pub mod migrate;
+pub mod rewards;
 
+pub use access::*;
 pub use mint_builder::*;
 pub use handle_payment::*;
+pub use migrate::*;
+pub use rewards::*;
 
 use anchor_lang::{
     prelude::*,
-    solana_program::{program_memory::sol_memcmp, pubkey::PUBKEY_BYTES},
+    solana_program::{keccak::hashv, program_memory::sol_memcmp, pubkey::PUBKEY_BYTES},
 };
-use crate::{state::RewardsConfig, error::ErrorCode};
-use spl_token::native_mint::ID as NativeMint;
+use crate::{state::{Guardian, Marketplace, ProductCreator, MAX_CREATORS}, error::ErrorCode};
 
 pub fn cmp_pubkeys(a: &Pubkey, b: &Pubkey) -> bool {
     sol_memcmp(a.as_ref(), b.as_ref(), PUBKEY_BYTES) == 0
 }
 
-/// Checks if marketplace reward system is active, is active when:
-/// If reward_mint == null_mint && rewardsEnabled == false -> NO REWARDS
-/// If reward_mint == null_mint && rewardsEnabled == true -> REWARDS (regardless of the reward_mint)
-/// If reward_mint == mint && rewardsEnabled == true -> REWARDS only with specific reward_mint
-/// CANT BE NATIVE MINT (ie SOL), a PDA from my program cant transfer SOL because is not owned by SystemProgram
-pub fn is_rewards_active(reward_config: RewardsConfig, payment_mint: Pubkey, program_id: Pubkey) -> bool {
-    let null_seeds = &[b"null".as_ref()];
-    let account_address = Pubkey::find_program_address(null_seeds, &program_id);
-    
-    reward_config.rewards_enabled && !cmp_pubkeys(&payment_mint, &NativeMint)
-        && (cmp_pubkeys(&payment_mint, &reward_config.reward_mint) || cmp_pubkeys(&reward_config.reward_mint, &account_address.0))
+/// Fails if the marketplace or the whole program are paused.
+/// The guardian account may not be initialized, in that case only the marketplace flag is considered.
+pub fn assert_not_paused(marketplace: &Marketplace, guardian: &AccountInfo) -> Result<()> {
+    if marketplace.paused {
+        return Err(ErrorCode::Paused.into());
+    }
+
+    if cmp_pubkeys(guardian.owner, &crate::ID) && !guardian.data_is_empty() {
+        let guardian_data = guardian.try_borrow_data()?;
+        let guardian = Guardian::try_deserialize(&mut &guardian_data[..])?;
+        if guardian.paused {
+            return Err(ErrorCode::Paused.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Creators can be empty, in that case the seller is the only creator.
+/// Otherwise every creator needs a share and can only appear once.
+pub fn assert_creators(creators: &[ProductCreator]) -> std::result::Result<(), ErrorCode> {
+    if creators.is_empty() {
+        return Ok(());
+    }
+
+    let total_shares = creators.iter().map(|creator| creator.share as u16).sum::<u16>();
+    if creators.len() > MAX_CREATORS || total_shares != 100 {
+        return Err(ErrorCode::IncorrectCreators);
+    }
+
+    for (index, creator) in creators.iter().enumerate() {
+        if creator.share == 0 || creators[..index].iter().any(|previous| cmp_pubkeys(&previous.address, &creator.address)) {
+            return Err(ErrorCode::IncorrectCreators);
+        }
+    }
+
+    Ok(())
+}
+
+/// Verifies a keccak merkle proof, each pair of nodes is sorted before hashing
+/// so the proof does not need to include the position of the nodes.
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let computed_root = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            hashv(&[&node, sibling]).0
+        } else {
+            hashv(&[sibling, &node]).0
+        }
+    });
+
+    computed_root == root
 }
 
 pub fn assert_ata(
@@ -62,4 +107,76 @@ pub fn assert_authority(account_property: &Pubkey, auth: &Pubkey) -> Result<()>
     } else {
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_pair(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        if a <= b { hashv(&[&a, &b]).0 } else { hashv(&[&b, &a]).0 }
+    }
+
+    // allowlist of 4 wallets, leaves are the keccak hash of each wallet like in the access gates
+    fn allowlist() -> (Vec<[u8; 32]>, [u8; 32], [u8; 32], [u8; 32]) {
+        let leaves = (0..4).map(|_| hashv(&[Pubkey::new_unique().as_ref()]).0).collect::<Vec<_>>();
+        let left = hash_pair(leaves[0], leaves[1]);
+        let right = hash_pair(leaves[2], leaves[3]);
+        (leaves, left, right, hash_pair(left, right))
+    }
+
+    #[test]
+    fn merkle_proofs_of_every_leaf_are_valid() {
+        let (leaves, left, right, root) = allowlist();
+        assert!(verify_merkle_proof(&[leaves[1], right], root, leaves[0]));
+        assert!(verify_merkle_proof(&[leaves[0], right], root, leaves[1]));
+        assert!(verify_merkle_proof(&[leaves[3], left], root, leaves[2]));
+        assert!(verify_merkle_proof(&[leaves[2], left], root, leaves[3]));
+        // a tree with a single leaf has the leaf as root and an empty proof
+        assert!(verify_merkle_proof(&[], leaves[0], leaves[0]));
+    }
+
+    #[test]
+    fn merkle_proofs_reject_wrong_leaves_proofs_and_roots() {
+        let (leaves, left, right, root) = allowlist();
+        let outsider = hashv(&[Pubkey::new_unique().as_ref()]).0;
+        assert!(!verify_merkle_proof(&[leaves[1], right], root, outsider));
+        assert!(!verify_merkle_proof(&[leaves[1], right], root, leaves[2]));
+        assert!(!verify_merkle_proof(&[leaves[1], left], root, leaves[0]));
+        assert!(!verify_merkle_proof(&[leaves[1]], root, leaves[0]));
+        assert!(!verify_merkle_proof(&[], root, leaves[0]));
+        assert!(!verify_merkle_proof(&[leaves[1], right], left, leaves[0]));
+        // intermediate nodes are not valid leaves
+        assert!(!verify_merkle_proof(&[right, leaves[1]], root, left));
+    }
+
+    fn creator(address: Pubkey, share: u8) -> ProductCreator {
+        ProductCreator { address, share }
+    }
+
+    #[test]
+    fn creators_shares_add_up_to_100() {
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert!(assert_creators(&[]).is_ok());
+        assert!(assert_creators(&[creator(first, 100)]).is_ok());
+        assert!(assert_creators(&[creator(first, 60), creator(second, 40)]).is_ok());
+        assert!(matches!(
+            assert_creators(&[creator(first, 60), creator(second, 30)]),
+            Err(ErrorCode::IncorrectCreators)
+        ));
+        let too_many = [20, 20, 20, 20, 10, 10].iter().map(|share| creator(Pubkey::new_unique(), *share)).collect::<Vec<_>>();
+        assert!(matches!(assert_creators(&too_many), Err(ErrorCode::IncorrectCreators)));
+    }
+
+    #[test]
+    fn creators_can_not_be_repeated_or_without_share() {
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert!(matches!(
+            assert_creators(&[creator(first, 50), creator(first, 50)]),
+            Err(ErrorCode::IncorrectCreators)
+        ));
+        assert!(matches!(
+            assert_creators(&[creator(first, 100), creator(second, 0)]),
+            Err(ErrorCode::IncorrectCreators)
+        ));
+    }
+}
@@ -0,0 +1,767 @@
+use {
+    crate::{
+        state::*,
+        error::ErrorCode,
+        events::RewardsExhaustedEvent,
+        utils::{cmp_pubkeys, assert_authority, transfer_checked_with_hook},
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::{mint_to, Mint, MintTo, TokenAccount},
+    spl_token::native_mint::ID as NativeMint,
+};
+
+/// Accounts involved in the rewards of a purchase, shared by the register_buy instructions.
+/// Most of them are optional, which ones are required depends on the campaign and the marketplace settings.
+pub struct RewardAccounts<'a, 'info> {
+    pub marketplace: &'a Account<'info, Marketplace>,
+    /// wallets of the seller and the buyer
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub payment_mint: &'a InterfaceAccount<'info, Mint>,
+    pub campaign: Option<&'a mut Account<'info, RewardCampaign>>,
+    pub payout_mint: Option<&'a InterfaceAccount<'info, Mint>>,
+    pub bounty_vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub seller_reward: Option<&'a mut Account<'info, Reward>>,
+    pub seller_reward_vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub seller_reward_ledger: Option<&'a mut Account<'info, RewardLedger>>,
+    pub buyer_reward: Option<&'a mut Account<'info, Reward>>,
+    pub buyer_reward_vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub buyer_reward_ledger: Option<&'a mut Account<'info, RewardLedger>>,
+    pub buyer_cashback_vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub referral: Option<Referral>,
+    pub referrer_reward: Option<&'a mut Account<'info, Reward>>,
+    pub referrer_reward_vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub referrer_reward_ledger: Option<&'a mut Account<'info, RewardLedger>>,
+    pub seller_points_vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub buyer_points_vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub referrer_points_vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub promotion: Option<&'a Account<'info, Promotion>>,
+    pub promotion_vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub buyer_promotion_vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: AccountInfo<'info>,
+    pub token_program_2022: Option<AccountInfo<'info>>,
+    pub payout_token_program: Option<AccountInfo<'info>>,
+    /// extra accounts of the transfer hooks of the payment mint and of the payout mint
+    pub payment_hook_accounts: &'a [AccountInfo<'info>],
+    pub payout_hook_accounts: &'a [AccountInfo<'info>],
+}
+
+/// Pays the rewards of the campaign for a purchase, nothing is paid when the campaign does not apply.
+/// When the budget or the bounty vault run out the purchase goes on with the rewards that are left.
+pub fn settle_rewards(accounts: &mut RewardAccounts, total_amount: u64, now: i64) -> Result<()> {
+    let marketplace = accounts.marketplace;
+    let rewards_config = &marketplace.rewards_config;
+    let campaign = match accounts.campaign.as_deref_mut() {
+        Some(campaign) => campaign,
+        None => return Ok(()),
+    };
+    let null_mint = get_null_mint(marketplace.bumps.null_bump)?;
+    if !is_rewards_active(campaign, &accounts.payment_mint.key(), now, &null_mint) {
+        return Ok(());
+    }
+
+    // rewards are paid in the payment mint unless the campaign has its own payout mint
+    let (reward_mint, reward_amount) = if cmp_pubkeys(&campaign.payout_mint, &Pubkey::default()) {
+        (accounts.payment_mint.to_account_info(), total_amount)
+    } else {
+        let payout_mint = accounts.payout_mint
+            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+        if !cmp_pubkeys(&payout_mint.key(), &campaign.payout_mint) {
+            return Err(ErrorCode::IncorrectMint.into());
+        }
+
+        let reward_amount = convert_to_payout(total_amount, campaign.payout_rate, accounts.payment_mint.decimals)?;
+        (payout_mint.to_account_info(), reward_amount)
+    };
+    let reward_mint_key = reward_mint.key();
+
+    let seller_bonus = calculate_bonus(campaign.seller_reward, reward_amount, campaign.max_reward)?;
+    let buyer_bonus = calculate_bonus(campaign.buyer_reward, reward_amount, campaign.max_reward)?;
+    let requested = seller_bonus.checked_add(buyer_bonus).ok_or(ErrorCode::NumericalOverflow)?;
+
+    let marketplace_seeds = &[
+        "marketplace".as_ref(),
+        marketplace.authority.as_ref(),
+        &[marketplace.bumps.bump],
+    ];
+
+    // the referrer of the buyer gets a share of the buyer bonus, so referring another wallet of the buyer pays nothing more.
+    // the share is not credited when the referrer has no reward vault, but the buyer does not get it back either
+    let referral = accounts.referral.as_ref()
+        .filter(|referral| is_referral_active(referral, rewards_config, now));
+    let referral_reward = if referral.is_some() { rewards_config.referral_reward } else { 0 };
+
+    // loyalty points are minted by the marketplace, they do not need a bounty vault
+    let (seller_bonus, buyer_bonus, referrer_bonus) = if is_points_mint(&campaign.payout_mint, rewards_config) {
+        let seller_points_vault = accounts.seller_points_vault
+            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+        let buyer_points_vault = accounts.buyer_points_vault
+            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+        let token_program_2022 = accounts.token_program_2022.clone()
+            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+        assert_vault(seller_points_vault, &accounts.seller, &reward_mint_key)?;
+        assert_vault(buyer_points_vault, &accounts.buyer, &reward_mint_key)?;
+
+        let available = campaign.budget.saturating_sub(campaign.spent);
+        let (seller_bonus, buyer_bonus) = limit_rewards(seller_bonus, buyer_bonus, available)?;
+        let (buyer_bonus, referrer_share) = split_referral(buyer_bonus, referral_reward)?;
+
+        mint_points(
+            token_program_2022.clone(),
+            reward_mint.clone(),
+            seller_points_vault.to_account_info(),
+            marketplace.to_account_info(),
+            seller_bonus,
+            &[&marketplace_seeds[..]],
+        )?;
+
+        mint_points(
+            token_program_2022.clone(),
+            reward_mint.clone(),
+            buyer_points_vault.to_account_info(),
+            marketplace.to_account_info(),
+            buyer_bonus,
+            &[&marketplace_seeds[..]],
+        )?;
+
+        let referrer_bonus = match (referral, accounts.referrer_points_vault) {
+            (Some(referral), Some(referrer_points_vault)) => {
+                assert_vault(referrer_points_vault, &referral.referrer, &reward_mint_key)?;
+
+                mint_points(
+                    token_program_2022,
+                    reward_mint.clone(),
+                    referrer_points_vault.to_account_info(),
+                    marketplace.to_account_info(),
+                    referrer_share,
+                    &[&marketplace_seeds[..]],
+                )?;
+
+                referrer_share
+            },
+            _ => 0,
+        };
+
+        (seller_bonus, buyer_bonus, referrer_bonus)
+    } else {
+        let seller_reward = accounts.seller_reward.as_deref_mut()
+            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+        assert_authority(&seller_reward.authority, &accounts.seller)?;
+        let seller_reward_vault = accounts.seller_reward_vault
+            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+        assert_vault(seller_reward_vault, &seller_reward.key(), &reward_mint_key)?;
+        let bounty_vault = accounts.bounty_vault
+            .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+        assert_vault(bounty_vault, &marketplace.key(), &reward_mint_key)?;
+        // the payout mint can belong to a different token program than the payment mint
+        let (reward_token_program, reward_hook_accounts) = if cmp_pubkeys(&reward_mint_key, &accounts.payment_mint.key()) {
+            (accounts.token_program.clone(), accounts.payment_hook_accounts)
+        } else {
+            let payout_token_program = accounts.payout_token_program.clone()
+                .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+            (payout_token_program, accounts.payout_hook_accounts)
+        };
+        if !cmp_pubkeys(&reward_token_program.key(), reward_mint.owner) {
+            return Err(ErrorCode::IncorrectTokenProgram.into());
+        }
+
+        let available = campaign.budget.saturating_sub(campaign.spent).min(bounty_vault.amount);
+        let (seller_bonus, buyer_bonus) = limit_rewards(seller_bonus, buyer_bonus, available)?;
+        let (buyer_bonus, referrer_share) = split_referral(buyer_bonus, referral_reward)?;
+
+        let referrer_vault = match (referral, accounts.referrer_reward_vault) {
+            (Some(referral), Some(referrer_reward_vault)) => {
+                let referrer_reward = accounts.referrer_reward.as_deref_mut()
+                    .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+                assert_authority(&referrer_reward.authority, &referral.referrer)?;
+                assert_vault(referrer_reward_vault, &referrer_reward.key(), &reward_mint_key)?;
+
+                Some(referrer_reward_vault)
+            },
+            _ => None,
+        };
+        let referrer_bonus = if referrer_vault.is_some() { referrer_share } else { 0 };
+
+        // the rewards can be withdrawn once every campaign that credited them has ended
+        if seller_bonus > 0 {
+            seller_reward.withdrawable_at = seller_reward.withdrawable_at.max(campaign.end);
+        }
+
+        // with instant cashback the buyer bonus is paid straight to the buyer, no reward account is needed
+        let buyer_vault = if rewards_config.instant_cashback {
+            let buyer_cashback_vault = accounts.buyer_cashback_vault
+                .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+            assert_vault(buyer_cashback_vault, &accounts.buyer, &reward_mint_key)?;
+
+            buyer_cashback_vault
+        } else {
+            let buyer_reward = accounts.buyer_reward.as_deref_mut()
+                .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+            assert_authority(&buyer_reward.authority, &accounts.buyer)?;
+            let buyer_reward_vault = accounts.buyer_reward_vault
+                .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+            assert_vault(buyer_reward_vault, &buyer_reward.key(), &reward_mint_key)?;
+            if buyer_bonus > 0 {
+                buyer_reward.withdrawable_at = buyer_reward.withdrawable_at.max(campaign.end);
+            }
+
+            buyer_reward_vault
+        };
+
+        if referrer_bonus > 0 {
+            if let Some(referrer_reward) = accounts.referrer_reward.as_deref_mut() {
+                referrer_reward.withdrawable_at = referrer_reward.withdrawable_at.max(campaign.end);
+            }
+        }
+
+        // with a vesting schedule the rewards are locked in the ledger of each vault, instant cashback is not vested
+        if rewards_config.vesting_duration > 0 {
+            let seller_reward_ledger = accounts.seller_reward_ledger.as_deref_mut()
+                .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+            lock_rewards(seller_reward_ledger, &seller_reward_vault.key(), seller_bonus, rewards_config, now)?;
+            if !rewards_config.instant_cashback {
+                let buyer_reward_ledger = accounts.buyer_reward_ledger.as_deref_mut()
+                    .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+                lock_rewards(buyer_reward_ledger, &buyer_vault.key(), buyer_bonus, rewards_config, now)?;
+            }
+            if let Some(referrer_vault) = referrer_vault {
+                let referrer_reward_ledger = accounts.referrer_reward_ledger.as_deref_mut()
+                    .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+                lock_rewards(referrer_reward_ledger, &referrer_vault.key(), referrer_bonus, rewards_config, now)?;
+            }
+        }
+
+        let mut transfers = vec![
+            (seller_reward_vault, seller_bonus),
+            (buyer_vault, buyer_bonus),
+        ];
+        if let Some(referrer_vault) = referrer_vault {
+            transfers.push((referrer_vault, referrer_bonus));
+        }
+        for (vault, bonus) in transfers {
+            transfer_checked_with_hook(
+                reward_token_program.clone(),
+                bounty_vault.to_account_info(),
+                reward_mint.clone(),
+                vault.to_account_info(),
+                marketplace.to_account_info(),
+                reward_hook_accounts,
+                bonus,
+                &[&marketplace_seeds[..]],
+            )?;
+        }
+
+        (seller_bonus, buyer_bonus, referrer_bonus)
+    };
+
+    let paid = seller_bonus + buyer_bonus + referrer_bonus;
+    if paid < requested {
+        emit!(RewardsExhaustedEvent {
+            campaign: campaign.key(),
+            buyer: accounts.buyer,
+            requested,
+            paid,
+        });
+    }
+    campaign.spent = campaign.spent
+        .checked_add(paid)
+        .ok_or(ErrorCode::NumericalOverflow)?;
+
+    Ok(())
+}
+
+/// Pays the bonus of the seller promotion of the product on top of the marketplace rewards, until the promotion vault runs out.
+pub fn pay_promotion(accounts: &RewardAccounts, total_amount: u64, now: i64) -> Result<()> {
+    let promotion = match accounts.promotion {
+        Some(promotion) if now >= promotion.start && now < promotion.end => promotion,
+        _ => return Ok(()),
+    };
+    let promotion_vault = accounts.promotion_vault
+        .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+    let buyer_promotion_vault = accounts.buyer_promotion_vault
+        .ok_or(ErrorCode::OptionalAccountNotProvided)?;
+    if !cmp_pubkeys(&promotion_vault.key(), &promotion.vault) {
+        return Err(ErrorCode::IncorrectATA.into());
+    }
+    assert_vault(promotion_vault, &promotion.key(), &accounts.payment_mint.key())?;
+    assert_vault(buyer_promotion_vault, &accounts.buyer, &accounts.payment_mint.key())?;
+
+    let payment_mint = accounts.payment_mint.to_account_info();
+    let bonus = calculate_bonus(promotion.buyer_reward, total_amount, 0)?.min(promotion_vault.amount);
+
+    let promotion_seeds = &[
+        b"promotion".as_ref(),
+        promotion.product.as_ref(),
+        promotion.id.as_ref(),
+        &[promotion.bump],
+    ];
+
+    transfer_checked_with_hook(
+        accounts.token_program.clone(),
+        promotion_vault.to_account_info(),
+        payment_mint,
+        buyer_promotion_vault.to_account_info(),
+        promotion.to_account_info(),
+        accounts.payment_hook_accounts,
+        bonus,
+        &[&promotion_seeds[..]],
+    )
+}
+
+/// Checks the owner and the mint of a token account used in the rewards.
+pub fn assert_vault(vault: &InterfaceAccount<TokenAccount>, owner: &Pubkey, mint: &Pubkey) -> Result<()> {
+    if !cmp_pubkeys(&vault.owner, owner) {
+        return Err(ErrorCode::IncorrectAuthority.into());
+    }
+    if !cmp_pubkeys(&vault.mint, mint) {
+        return Err(ErrorCode::IncorrectATA.into());
+    }
+
+    Ok(())
+}
+
+/// Checks if a reward campaign applies to a purchase, the purchase has to be made between start and end and:
+/// If reward_mint == null_mint -> REWARDS (regardless of the payment mint)
+/// If reward_mint == mint -> REWARDS only with specific reward_mint
+/// Payments with NATIVE MINT (ie SOL) are only rewarded if the campaign has a payout mint,
+/// a PDA from my program cant transfer SOL because is not owned by SystemProgram
+pub fn is_rewards_active(campaign: &RewardCampaign, payment_mint: &Pubkey, now: i64, null_mint: &Pubkey) -> bool {
+    let has_payout_mint = !cmp_pubkeys(&campaign.payout_mint, &Pubkey::default());
+
+    now >= campaign.start && now < campaign.end && (has_payout_mint || !cmp_pubkeys(payment_mint, &NativeMint))
+        && (cmp_pubkeys(payment_mint, &campaign.reward_mint) || cmp_pubkeys(&campaign.reward_mint, null_mint))
+}
+
+/// Address of the "null" PDA, derived with the bump stored in the marketplace to avoid
+/// searching for it in every purchase.
+pub fn get_null_mint(null_bump: u8) -> Result<Pubkey> {
+    Pubkey::create_program_address(&[b"null".as_ref(), &[null_bump]], &crate::ID)
+        .map_err(|_| ErrorCode::IncorrectSeeds.into())
+}
+
+/// Converts an amount of the payment mint to the payout mint of a campaign,
+/// the rate is the amount of payout tokens (base units) given per whole payment token.
+pub fn convert_to_payout(amount: u64, payout_rate: u64, payment_decimals: u8) -> std::result::Result<u64, ErrorCode> {
+    let payout_amount = (amount as u128)
+        .checked_mul(payout_rate as u128)
+        .ok_or(ErrorCode::NumericalOverflow)?
+        .checked_div(10u128.pow(payment_decimals as u32))
+        .ok_or(ErrorCode::NumericalOverflow)?;
+
+    u64::try_from(payout_amount).map_err(|_| ErrorCode::NumericalOverflow)
+}
+
+/// Calculates the reward of a purchase, limited to max_reward when it is not 0.
+pub fn calculate_bonus(reward: u16, amount: u64, max_reward: u64) -> std::result::Result<u64, ErrorCode> {
+    let bonus = (reward as u128)
+        .checked_mul(amount as u128)
+        .ok_or(ErrorCode::NumericalOverflow)?
+        .checked_div(10000)
+        .ok_or(ErrorCode::NumericalOverflow)? as u64;
+
+    if max_reward > 0 {
+        Ok(bonus.min(max_reward))
+    } else {
+        Ok(bonus)
+    }
+}
+
+/// Shares what is available between seller and buyer in proportion to their rewards when it can not cover both.
+pub fn limit_rewards(seller_bonus: u64, buyer_bonus: u64, available: u64) -> std::result::Result<(u64, u64), ErrorCode> {
+    let requested = (seller_bonus as u128)
+        .checked_add(buyer_bonus as u128)
+        .ok_or(ErrorCode::NumericalOverflow)?;
+    if requested <= available as u128 {
+        return Ok((seller_bonus, buyer_bonus));
+    }
+
+    let buyer_share = (buyer_bonus as u128)
+        .checked_mul(available as u128)
+        .ok_or(ErrorCode::NumericalOverflow)?
+        .checked_div(requested)
+        .ok_or(ErrorCode::NumericalOverflow)? as u64;
+
+    Ok((available - buyer_share, buyer_share))
+}
+
+/// Amount of the ledger rewards that has not vested yet following the schedule recorded in the ledger.
+pub fn calculate_locked(ledger: &RewardLedger, now: i64) -> std::result::Result<u64, ErrorCode> {
+    if now < ledger.cliff_end {
+        return Ok(ledger.locked);
+    }
+    let elapsed = now.saturating_sub(ledger.vesting_start);
+    if elapsed >= ledger.vesting_duration {
+        return Ok(0);
+    }
+
+    let vested = (ledger.locked as u128)
+        .checked_mul(elapsed as u128)
+        .ok_or(ErrorCode::NumericalOverflow)?
+        .checked_div(ledger.vesting_duration as u128)
+        .ok_or(ErrorCode::NumericalOverflow)? as u64;
+
+    Ok(ledger.locked - vested)
+}
+
+/// Adds new rewards to the vesting schedule of a vault.
+/// The start of the schedule moves to the average of the credits weighted by their amount,
+/// so what has already vested stays vested and new rewards do not restart the schedule.
+pub fn lock_rewards(
+    ledger: &mut RewardLedger,
+    vault: &Pubkey,
+    amount: u64,
+    rewards_config: &RewardsConfig,
+    now: i64,
+) -> Result<()> {
+    if !cmp_pubkeys(&ledger.vault, vault) {
+        return Err(ErrorCode::IncorrectATA.into());
+    }
+
+    // a new schedule follows the current settings of the marketplace, they can not change a running one
+    let elapsed = now.saturating_sub(ledger.vesting_start);
+    if ledger.locked == 0 || elapsed >= ledger.vesting_duration {
+        ledger.locked = amount;
+        ledger.vesting_start = now;
+        ledger.cliff_end = now.checked_add(rewards_config.vesting_cliff).ok_or(ErrorCode::NumericalOverflow)?;
+        ledger.vesting_duration = rewards_config.vesting_duration;
+
+        return Ok(());
+    }
+
+    let locked = ledger.locked.checked_add(amount).ok_or(ErrorCode::NumericalOverflow)?;
+    let vesting_start = ((ledger.locked as i128) * (ledger.vesting_start as i128) + (amount as i128) * (now as i128))
+        .checked_div(locked as i128)
+        .ok_or(ErrorCode::NumericalOverflow)? as i64;
+
+    // before the cliff it moves with the start, once it has passed the new rewards can not lock again what has vested
+    if now < ledger.cliff_end {
+        let vesting_cliff = ledger.cliff_end - ledger.vesting_start;
+        ledger.cliff_end = vesting_start.checked_add(vesting_cliff).ok_or(ErrorCode::NumericalOverflow)?;
+    }
+    ledger.locked = locked;
+    ledger.vesting_start = vesting_start;
+
+    Ok(())
+}
+
+/// Splits the buyer bonus between the buyer and the referrer, returns the buyer and the referrer shares.
+pub fn split_referral(buyer_bonus: u64, referral_reward: u16) -> std::result::Result<(u64, u64), ErrorCode> {
+    let referrer_share = calculate_bonus(referral_reward, buyer_bonus, 0)?.min(buyer_bonus);
+
+    Ok((buyer_bonus - referrer_share, referrer_share))
+}
+
+/// Deserializes the referral of the buyer, returns None when the buyer did not register one.
+pub fn load_referral(referral: &AccountInfo) -> Result<Option<Referral>> {
+    if !cmp_pubkeys(referral.owner, &crate::ID) || referral.data_is_empty() {
+        return Ok(None);
+    }
+
+    let referral_data = referral.try_borrow_data()?;
+    Ok(Some(Referral::try_deserialize(&mut &referral_data[..])?))
+}
+
+/// Checks if the referrer of a buyer is still credited, referrals expire after the marketplace referral duration.
+pub fn is_referral_active(referral: &Referral, rewards_config: &RewardsConfig, now: i64) -> bool {
+    rewards_config.referral_reward > 0 && now < referral.created_at.saturating_add(rewards_config.referral_duration)
+}
+
+/// Checks if the campaign pays the rewards with the loyalty points of the marketplace.
+pub fn is_points_mint(payout_mint: &Pubkey, rewards_config: &RewardsConfig) -> bool {
+    !cmp_pubkeys(&rewards_config.points_mint, &Pubkey::default()) && cmp_pubkeys(payout_mint, &rewards_config.points_mint)
+}
+
+/// Calculates the discount obtained by spending points, the marketplace funds it with its fee
+/// so the seller always receives the full price.
+pub fn calculate_discount(amount: u64, discount: u16, total_fee: u64) -> std::result::Result<u64, ErrorCode> {
+    let discount_amount = (discount.min(10000) as u128)
+        .checked_mul(amount as u128)
+        .ok_or(ErrorCode::NumericalOverflow)?
+        .checked_div(10000)
+        .ok_or(ErrorCode::NumericalOverflow)? as u64;
+
+    Ok(discount_amount.min(total_fee))
+}
+
+/// Adds the discount of the spent points to the discount of the buyer, in basis points of the purchase.
+/// The marketplace funds it with its fee, so spending points above the fee of the payment mint is rejected.
+pub fn calculate_points_discount(
+    discount: u16,
+    points: u64,
+    points_discount: u16,
+    fee: u16,
+) -> std::result::Result<u16, ErrorCode> {
+    let discount = (points as u128)
+        .checked_mul(points_discount as u128)
+        .and_then(|points_discount| points_discount.checked_add(discount as u128))
+        .ok_or(ErrorCode::NumericalOverflow)?;
+
+    if discount > fee as u128 {
+        return Err(ErrorCode::DiscountAboveFee);
+    }
+
+    Ok(discount as u16)
+}
+
+/// Mints loyalty points, the marketplace is the mint authority.
+pub fn mint_points<'info>(
+    token_program: AccountInfo<'info>,
+    points_mint: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    marketplace: AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    mint_to(
+        CpiContext::new_with_signer(
+            token_program,
+            MintTo {
+                mint: points_mint,
+                to,
+                authority: marketplace,
+            },
+            signer_seeds,
+        ),
+        amount,
+    ).map_err(|_| ErrorCode::MintToError.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn campaign(reward_mint: Pubkey, payout_mint: Pubkey) -> RewardCampaign {
+        RewardCampaign {
+            marketplace: Pubkey::new_unique(),
+            id: [0; 16],
+            start: 100,
+            end: 200,
+            budget: 1_000,
+            spent: 0,
+            max_reward: 0,
+            reward_mint,
+            payout_mint,
+            payout_rate: 0,
+            seller_reward: 100,
+            buyer_reward: 100,
+            bump: 255,
+        }
+    }
+
+    fn rewards_config(vesting_cliff: i64, vesting_duration: i64) -> RewardsConfig {
+        RewardsConfig {
+            open_campaigns: 0,
+            vesting_cliff,
+            vesting_duration,
+            points_mint: Pubkey::default(),
+            points_discount: 0,
+            instant_cashback: false,
+            referral_reward: 1000,
+            referral_duration: 50,
+        }
+    }
+
+    fn null_mint() -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"null".as_ref()], &crate::ID)
+    }
+
+    #[test]
+    fn null_mint_is_rebuilt_from_the_bump() {
+        let (address, bump) = null_mint();
+        assert_eq!(get_null_mint(bump).unwrap(), address);
+    }
+
+    #[test]
+    fn rewards_are_active_between_start_and_end() {
+        let payment_mint = Pubkey::new_unique();
+        let campaign = campaign(payment_mint, Pubkey::default());
+        let (null_mint, _) = null_mint();
+
+        assert!(!is_rewards_active(&campaign, &payment_mint, 99, &null_mint));
+        assert!(is_rewards_active(&campaign, &payment_mint, 100, &null_mint));
+        assert!(is_rewards_active(&campaign, &payment_mint, 199, &null_mint));
+        assert!(!is_rewards_active(&campaign, &payment_mint, 200, &null_mint));
+    }
+
+    #[test]
+    fn rewards_depend_on_the_payment_mint() {
+        let payment_mint = Pubkey::new_unique();
+        let (null_mint, _) = null_mint();
+
+        let specific = campaign(Pubkey::new_unique(), Pubkey::default());
+        assert!(!is_rewards_active(&specific, &payment_mint, 150, &null_mint));
+
+        let any = campaign(null_mint, Pubkey::default());
+        assert!(is_rewards_active(&any, &payment_mint, 150, &null_mint));
+        // SOL can not be paid from a bounty vault, only campaigns with a payout mint reward it
+        assert!(!is_rewards_active(&any, &NativeMint, 150, &null_mint));
+
+        let with_payout = campaign(null_mint, Pubkey::new_unique());
+        assert!(is_rewards_active(&with_payout, &NativeMint, 150, &null_mint));
+    }
+
+    #[test]
+    fn bonus_is_limited_by_max_reward() {
+        assert_eq!(calculate_bonus(250, 10_000, 0).unwrap(), 250);
+        assert_eq!(calculate_bonus(250, 10_000, 100).unwrap(), 100);
+        assert_eq!(calculate_bonus(10000, u64::MAX, 0).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn payout_uses_whole_payment_tokens() {
+        // 10 tokens of 6 decimals per whole token of 6 decimals
+        assert_eq!(convert_to_payout(2_500_000, 10_000_000, 6).unwrap(), 25_000_000);
+        assert_eq!(convert_to_payout(1, 1, 9).unwrap(), 0);
+        assert!(convert_to_payout(u64::MAX, u64::MAX, 0).is_err());
+    }
+
+    #[test]
+    fn limited_rewards_are_shared_in_proportion() {
+        assert_eq!(limit_rewards(30, 10, 100).unwrap(), (30, 10));
+        assert_eq!(limit_rewards(30, 10, 20).unwrap(), (15, 5));
+        assert_eq!(limit_rewards(30, 10, 0).unwrap(), (0, 0));
+    }
+
+    fn ledger(vault: Pubkey, locked: u64, cliff_end: i64, vesting_duration: i64) -> RewardLedger {
+        RewardLedger {
+            reward: Pubkey::new_unique(),
+            vault,
+            locked,
+            vesting_start: 0,
+            bump: 255,
+            cliff_end,
+            vesting_duration,
+        }
+    }
+
+    #[test]
+    fn locked_rewards_vest_after_the_cliff() {
+        let ledger = ledger(Pubkey::new_unique(), 1_000, 10, 100);
+
+        assert_eq!(calculate_locked(&ledger, 9).unwrap(), 1_000);
+        assert_eq!(calculate_locked(&ledger, 10).unwrap(), 900);
+        assert_eq!(calculate_locked(&ledger, 50).unwrap(), 500);
+        assert_eq!(calculate_locked(&ledger, 100).unwrap(), 0);
+    }
+
+    #[test]
+    fn new_rewards_keep_what_has_vested() {
+        let config = rewards_config(0, 100);
+        let vault = Pubkey::new_unique();
+        let mut ledger = ledger(vault, 1_000, 0, 100);
+
+        lock_rewards(&mut ledger, &vault, 1_000, &config, 50).unwrap();
+        assert_eq!(ledger.locked, 2_000);
+        assert_eq!(ledger.vesting_start, 25);
+        assert_eq!(calculate_locked(&ledger, 50).unwrap(), 1_500);
+        assert_eq!(calculate_locked(&ledger, 100).unwrap(), 500);
+        assert!(lock_rewards(&mut ledger, &Pubkey::new_unique(), 100, &config, 50).is_err());
+    }
+
+    #[test]
+    fn new_rewards_do_not_lock_again_after_the_cliff() {
+        let config = rewards_config(50, 100);
+        let vault = Pubkey::new_unique();
+        let mut ledger = ledger(vault, 1_000, 50, 100);
+
+        assert_eq!(calculate_locked(&ledger, 60).unwrap(), 400);
+        lock_rewards(&mut ledger, &vault, 1_000, &config, 60).unwrap();
+        assert_eq!(ledger.cliff_end, 50);
+        assert_eq!(calculate_locked(&ledger, 60).unwrap(), 1_400);
+    }
+
+    #[test]
+    fn the_cliff_moves_with_the_start_until_it_passes() {
+        let config = rewards_config(50, 100);
+        let vault = Pubkey::new_unique();
+        let mut ledger = ledger(vault, 1_000, 50, 100);
+
+        lock_rewards(&mut ledger, &vault, 1_000, &config, 20).unwrap();
+        assert_eq!(ledger.vesting_start, 10);
+        assert_eq!(ledger.cliff_end, 60);
+        assert_eq!(calculate_locked(&ledger, 59).unwrap(), 2_000);
+        assert_eq!(calculate_locked(&ledger, 60).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn steady_rewards_keep_vesting() {
+        let config = rewards_config(0, 100);
+        let vault = Pubkey::new_unique();
+        let mut ledger = ledger(vault, 0, 0, 0);
+
+        for now in (0..1_000).step_by(10) {
+            lock_rewards(&mut ledger, &vault, 100, &config, now).unwrap();
+        }
+        // 10_000 were credited, only the rewards of the last duration can be locked
+        assert!(calculate_locked(&ledger, 990).unwrap() <= 1_000);
+    }
+
+    #[test]
+    fn running_schedules_ignore_new_settings() {
+        let vault = Pubkey::new_unique();
+        let mut ledger = ledger(vault, 1_000, 0, 100);
+
+        lock_rewards(&mut ledger, &vault, 1_000, &rewards_config(500, 1_000), 50).unwrap();
+        assert_eq!((ledger.cliff_end, ledger.vesting_duration), (0, 100));
+
+        lock_rewards(&mut ledger, &vault, 1_000, &rewards_config(500, 1_000), 200).unwrap();
+        assert_eq!(ledger.locked, 1_000);
+        assert_eq!((ledger.cliff_end, ledger.vesting_duration), (700, 1_000));
+    }
+
+    #[test]
+    fn referrer_share_comes_from_the_buyer_bonus() {
+        assert_eq!(split_referral(1_000, 1_000).unwrap(), (900, 100));
+        assert_eq!(split_referral(1_000, 0).unwrap(), (1_000, 0));
+        assert_eq!(split_referral(1_000, 10_000).unwrap(), (0, 1_000));
+        assert_eq!(split_referral(0, 1_000).unwrap(), (0, 0));
+    }
+
+    #[test]
+    fn referrals_expire_after_the_duration() {
+        let mut config = rewards_config(0, 0);
+        let referral = Referral {
+            authority: Pubkey::new_unique(),
+            marketplace: Pubkey::new_unique(),
+            referrer: Pubkey::new_unique(),
+            created_at: 100,
+            bump: 255,
+        };
+
+        assert!(is_referral_active(&referral, &config, 149));
+        assert!(!is_referral_active(&referral, &config, 150));
+        config.referral_reward = 0;
+        assert!(!is_referral_active(&referral, &config, 120));
+    }
+
+    #[test]
+    fn points_mint_is_not_matched_before_it_is_created() {
+        let mut config = rewards_config(0, 0);
+        assert!(!is_points_mint(&Pubkey::default(), &config));
+
+        let points_mint = Pubkey::new_unique();
+        config.points_mint = points_mint;
+        assert!(is_points_mint(&points_mint, &config));
+        assert!(!is_points_mint(&Pubkey::new_unique(), &config));
+    }
+
+    #[test]
+    fn discount_is_limited_to_the_fee() {
+        assert_eq!(calculate_discount(1_000, 250, 500).unwrap(), 25);
+        assert_eq!(calculate_discount(1_000, 2_500, 50).unwrap(), 50);
+        assert_eq!(calculate_discount(1_000, u16::MAX, 2_000).unwrap(), 1_000);
+        assert_eq!(calculate_discount(1_000, 2_500, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn points_can_not_be_spent_above_the_fee() {
+        assert_eq!(calculate_points_discount(0, 2, 100, 250).unwrap(), 200);
+        assert_eq!(calculate_points_discount(200, 1, 50, 250).unwrap(), 250);
+        assert!(matches!(calculate_points_discount(200, 1, 100, 250), Err(ErrorCode::DiscountAboveFee)));
+        assert!(matches!(calculate_points_discount(0, u64::MAX, 100, 250), Err(ErrorCode::DiscountAboveFee)));
+    }
+}
@@ -8,6 +8,11 @@ import {
   TOKEN_PROGRAM_ID,
   getOrCreateAssociatedTokenAccount,
   getAccount,
+  getMintLen,
+  mintTo,
+  ExtensionType,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
   createTransferInstruction,
   getAssociatedTokenAddressSync,
   ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -63,6 +68,12 @@ describe("marketplace_manager", () => {
   let productPubkey: anchor.web3.PublicKey;
   let sellerReward: anchor.web3.PublicKey;
   let buyerReward: anchor.web3.PublicKey;
+  let campaign: anchor.web3.PublicKey;
+  let campaignEnd: number;
+  const [guardianPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("guardian", "utf-8")],
+    program.programId
+  );
 
   // Marketplace properties:
   let discountMint: anchor.web3.PublicKey;
@@ -73,7 +84,6 @@ describe("marketplace_manager", () => {
   let buyerRewardMarketplace: number;
   let transferable: boolean;
   let permissionless: boolean;
-  let rewardsEnabled: boolean;
   let accessMint: anchor.web3.PublicKey;
   let accessMintBump: number;
   const FeePayer = {
@@ -92,6 +102,255 @@ describe("marketplace_manager", () => {
   let treeAuthority: anchor.web3.PublicKey;
   let bubblegumSigner: anchor.web3.PublicKey;
 
+  // creates a campaign that rewards the payments made with rewardMint during the next `duration` seconds
+  const initCampaign = async (rewardMint: anchor.web3.PublicKey, duration: number): Promise<[anchor.web3.PublicKey, number]> => {
+    const campaignId = parse(uuid());
+    const [campaignPubkey] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("campaign", "utf-8"),
+        marketplacePubkey.toBuffer(),
+        campaignId,
+      ],
+      program.programId
+    );
+    const now = Math.floor(Date.now() / 1000);
+    const end = now + duration;
+
+    await program.methods
+      .initCampaign({
+        id: [...campaignId],
+        start: new BN(now - 60),
+        end: new BN(end),
+        budget: new BN(1000000),
+        maxReward: new BN(0),
+        payoutRate: new BN(0),
+        sellerReward: sellerRewardMarketplace,
+        buyerReward: buyerRewardMarketplace,
+      })
+      .accounts({
+        systemProgram: SystemProgram.programId,
+        signer: marketplaceAuth.publicKey,
+        marketplace: marketplacePubkey,
+        rewardMint: rewardMint,
+        payoutMint: null,
+        campaign: campaignPubkey,
+      })
+      .signers([marketplaceAuth])
+      .rpc(confirmOptions)
+      .catch(console.error);
+
+    return [campaignPubkey, end];
+  };
+
+  const getPaymentMintConfig = (mint: anchor.web3.PublicKey): anchor.web3.PublicKey => {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("payment_mint", "utf-8"),
+        marketplacePubkey.toBuffer(),
+        mint.toBuffer()
+      ],
+      program.programId
+    )[0];
+  };
+
+  const getReferral = (wallet: anchor.web3.PublicKey): anchor.web3.PublicKey => {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("referral", "utf-8"),
+        wallet.toBuffer(),
+        marketplacePubkey.toBuffer()
+      ],
+      program.programId
+    )[0];
+  };
+
+  const getBlockedWallet = (wallet: anchor.web3.PublicKey): anchor.web3.PublicKey => {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("blocked_wallet", "utf-8"),
+        marketplacePubkey.toBuffer(),
+        wallet.toBuffer()
+      ],
+      program.programId
+    )[0];
+  };
+
+  const getSellerAccess = (wallet: anchor.web3.PublicKey): anchor.web3.PublicKey => {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("seller_access", "utf-8"),
+        wallet.toBuffer(),
+        marketplacePubkey.toBuffer()
+      ],
+      program.programId
+    )[0];
+  };
+
+  // creates a fungible product paid with paymentMints[0] by default, token-gated marketplaces need the access vault of the seller
+  const initProduct = async (
+    productSeller: anchor.web3.Keypair,
+    accessVault: anchor.web3.PublicKey | null,
+    royalty = 0,
+    paymentMint = paymentMints[0],
+    buyerGate: Record<string, any> | null = null,
+    creators: Record<string, any>[] = [],
+  ): Promise<[anchor.web3.PublicKey, anchor.web3.PublicKey]> => {
+    const productId = parse(uuid());
+    const [product] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("product", "utf-8"), productId],
+      program.programId
+    );
+    const [mint, bump] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("product_mint", "utf-8"), product.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initProduct({
+        id: [...productId],
+        productPrice: new BN(100),
+        royalty: royalty,
+        creators: creators,
+        extensions: [],
+        productMintBump: bump,
+        proof: [],
+        buyerGate: buyerGate,
+      })
+      .accounts({
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+        signer: productSeller.publicKey,
+        marketplace: marketplacePubkey,
+        product: product,
+        sellerAccess: getSellerAccess(productSeller.publicKey),
+        blockedSeller: getBlockedWallet(productSeller.publicKey),
+        guardian: guardianPubkey,
+        paymentMintConfig: getPaymentMintConfig(paymentMint),
+        productMint: mint,
+        paymentMint: paymentMint,
+        accessMint: accessVault ? accessMint : null,
+        accessVault: accessVault,
+        gateMint: null,
+        gateMetadata: null,
+        gateClaim: null,
+      })
+      .signers([productSeller])
+      .rpc(confirmOptions);
+
+    return [product, mint];
+  };
+
+  // buys product tokens of a product created with initProduct, returns the product token vault of the buyer
+  const buyProductTokens = async (
+    productBuyer: anchor.web3.Keypair,
+    productSeller: anchor.web3.PublicKey,
+    product: anchor.web3.PublicKey,
+    mint: anchor.web3.PublicKey,
+    amount: number,
+    paymentMint = paymentMints[0],
+    paymentTokenProgram = TOKEN_PROGRAM_ID,
+    buyerGateVault: anchor.web3.PublicKey | null = null,
+    buyerGateMint: anchor.web3.PublicKey | null = null,
+  ): Promise<anchor.web3.PublicKey> => {
+    const buyerTokenVault = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      productBuyer,
+      mint,
+      productBuyer.publicKey,
+      false,
+      "confirmed",
+      confirmOptions,
+      TOKEN_2022_PROGRAM_ID,
+    );
+
+    await program.methods
+      .registerBuyFungible(amount, [], 0)
+      .accounts({
+        systemProgram: SystemProgram.programId,
+        tokenProgram: paymentTokenProgram,
+        signer: productBuyer.publicKey,
+        seller: null,
+        marketplaceAuth: null,
+        marketplace: marketplacePubkey,
+        guardian: guardianPubkey,
+        product: product,
+        sellerAccess: getSellerAccess(productSeller),
+        blockedBuyer: getBlockedWallet(productBuyer.publicKey),
+        blockedSeller: getBlockedWallet(productSeller),
+        buyerGateVault: buyerGateVault,
+        buyerGateMint: buyerGateMint,
+        buyerGateMetadata: null,
+        productMint: mint,
+        paymentMint: paymentMint,
+        paymentMintConfig: getPaymentMintConfig(paymentMint),
+        buyerTokenVault: buyerTokenVault.address,
+        buyerTransferVault: getAssociatedTokenAddressSync(paymentMint, productBuyer.publicKey, false, paymentTokenProgram),
+        sellerTransferVault: getAssociatedTokenAddressSync(paymentMint, productSeller, false, paymentTokenProgram),
+        marketplaceTransferVault: getAssociatedTokenAddressSync(paymentMint, marketplaceAuth.publicKey, false, paymentTokenProgram),
+        campaign: null,
+        payoutMint: null,
+        payoutTokenProgram: null,
+        bountyVault: null,
+        sellerReward: null,
+        sellerRewardVault: null,
+        sellerRewardLedger: null,
+        buyerReward: null,
+        buyerRewardVault: null,
+        buyerRewardLedger: null,
+        buyerCashbackVault: null,
+        referral: getReferral(productBuyer.publicKey),
+        referrerReward: null,
+        referrerRewardVault: null,
+        referrerRewardLedger: null,
+        sellerPointsVault: null,
+        buyerPointsVault: null,
+        referrerPointsVault: null,
+        pointsDiscount: null,
+        promotion: null,
+        promotionVault: null,
+        buyerPromotionVault: null,
+        tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([productBuyer])
+      .rpc(confirmOptions);
+
+    return buyerTokenVault.address;
+  };
+
+  // edits the token-gated marketplace used by the last tests, only the given settings change from their defaults
+  const editMarketplace = async (settings: Record<string, any>) => {
+    await program.methods
+      .editMarketplace({
+        fee: fee,
+        feeReduction: feeReduction,
+        transferable: transferable,
+        permissionless: permissionless,
+        requestExpiry: new BN(0),
+        accessGate: { accessMint: {} },
+        accessMintBump: accessMintBump,
+        feePayer: FeePayer.Buyer,
+        restrictedPaymentMints: false,
+        vestingCliff: new BN(0),
+        vestingDuration: new BN(0),
+        instantCashback: false,
+        referralReward: 0,
+        referralDuration: new BN(0),
+        ...settings,
+      })
+      .accounts({
+        signer: marketplaceAuth.publicKey,
+        marketplace: marketplacePubkey,
+        discountMint: discountMint,
+      })
+      .signers([marketplaceAuth])
+      .rpc(confirmOptions);
+  };
+
+  const waitCampaignEnd = async (end: number) => {
+    await delay(Math.max(0, end * 1000 - Date.now()) + 2000);
+  };
+
   it("Should create marketplace account", async () => {
     rewardMint = discountMint = paymentMints[0] = await createMint(provider, confirmOptions);
 
@@ -117,7 +376,7 @@ describe("marketplace_manager", () => {
     bountyVaults.push([bountyVault, 0])
 
     fee = feeReduction = sellerRewardMarketplace = buyerRewardMarketplace = 0;
-    transferable = rewardsEnabled = false;
+    transferable = false;
     permissionless = true;
 
     [accessMint, accessMintBump] = anchor.web3.PublicKey.findProgramAddressSync(
@@ -131,13 +390,18 @@ describe("marketplace_manager", () => {
     const initMarketplaceParams = {
       fee: fee,
       feeReduction: feeReduction,
-      sellerReward: sellerRewardMarketplace,
-      buyerReward: buyerRewardMarketplace,
       transferable: transferable,
       permissionless: permissionless,
-      rewardsEnabled: rewardsEnabled,
+      requestExpiry: new BN(0),
+      accessGate: { accessMint: {} },
       accessMintBump: accessMintBump,
       feePayer: FeePayer.Seller,
+      restrictedPaymentMints: false,
+      vestingCliff: new BN(0),
+      vestingDuration: new BN(0),
+      instantCashback: false,
+      referralReward: 0,
+      referralDuration: new BN(0),
     };
     const initMarketplaceAccounts = {
       systemProgram: SystemProgram.programId,
@@ -168,10 +432,7 @@ describe("marketplace_manager", () => {
     assert.equal(marketplaceAccount.feesConfig.fee, fee);
     assert.equal(marketplaceAccount.feesConfig.feeReduction, feeReduction);
     assert.equal(marketplaceAccount.feesConfig.feePayer.toString(), FeePayer.Seller.toString());
-    assert.equal(marketplaceAccount.rewardsConfig.rewardMint.toString(), rewardMint.toString());
-    assert.equal(marketplaceAccount.rewardsConfig.sellerReward, sellerRewardMarketplace);
-    assert.equal(marketplaceAccount.rewardsConfig.buyerReward, buyerRewardMarketplace);
-    assert.equal(marketplaceAccount.rewardsConfig.rewardsEnabled, rewardsEnabled);
+    assert.equal(marketplaceAccount.rewardsConfig.openCampaigns, 0);
 
     /// marketplace pda is created with "marketpalce" and signer address, lets try to create the same pda
     /// another user cant create the previous marketplace and authority cant be changed
@@ -192,18 +453,22 @@ describe("marketplace_manager", () => {
     const editMarketplaceInfoParams = {
       fee: 100,
       feeReduction: 100,
-      sellerReward: 100,
-      buyerReward: 100,
       transferable: !transferable,
       permissionless: !permissionless,
-      rewardsEnabled: !rewardsEnabled,
+      requestExpiry: new BN(0),
+      accessGate: { accessMint: {} },
       feePayer: FeePayer.Buyer,
+      restrictedPaymentMints: false,
+      vestingCliff: new BN(0),
+      vestingDuration: new BN(0),
+      instantCashback: false,
+      referralReward: 0,
+      referralDuration: new BN(0),
     };
 
     const editMarketplaceInfoAccounts = {
       signer: marketplaceAuth.publicKey,
       marketplace: marketplacePubkey,
-      rewardMint: await createMint(provider, confirmOptions),
       discountMint: await createMint(provider, confirmOptions),
     };
 
@@ -224,10 +489,6 @@ describe("marketplace_manager", () => {
     assert.equal(changedMarketplaceAccount.feesConfig.feePayer.toString(), FeePayer.Buyer.toString());
     assert.equal(changedMarketplaceAccount.feesConfig.fee, 100);
     assert.equal(changedMarketplaceAccount.feesConfig.feeReduction, 100);
-    assert.equal(changedMarketplaceAccount.rewardsConfig.rewardMint.toString(), editMarketplaceInfoAccounts.rewardMint.toString());
-    assert.equal(changedMarketplaceAccount.rewardsConfig.sellerReward, 100);
-    assert.equal(changedMarketplaceAccount.rewardsConfig.buyerReward, 100);
-    assert.equal(changedMarketplaceAccount.rewardsConfig.rewardsEnabled, !rewardsEnabled);
 
     // another wallet tries to change product data
     const balance = 1000;
@@ -235,17 +496,21 @@ describe("marketplace_manager", () => {
     const exploiterEditInfoParams = {
       fee: fee,
       feeReduction: feeReduction,
-      sellerReward: sellerRewardMarketplace,
-      buyerReward: buyerRewardMarketplace,
       transferable: transferable,
       permissionless: permissionless,
-      rewardsEnabled: rewardsEnabled,
+      requestExpiry: new BN(0),
+      accessGate: { accessMint: {} },
       feePayer: FeePayer.Seller,
+      restrictedPaymentMints: false,
+      vestingCliff: new BN(0),
+      vestingDuration: new BN(0),
+      instantCashback: false,
+      referralReward: 0,
+      referralDuration: new BN(0),
     };
     const exploiterEditInfoAccounts = {
       signer: exploiter.publicKey,
       marketplace: marketplacePubkey,
-      rewardMint: rewardMint,
       discountMint: discountMint,
     };
 
@@ -265,17 +530,21 @@ describe("marketplace_manager", () => {
     const initMarketplaceParams = {
       fee: fee,
       feeReduction: feeReduction,
-      sellerReward: sellerRewardMarketplace,
-      buyerReward: buyerRewardMarketplace,
       transferable: transferable,
       permissionless: permissionless,
-      rewardsEnabled: rewardsEnabled,
+      requestExpiry: new BN(0),
+      accessGate: { accessMint: {} },
       feePayer: FeePayer.Seller,
+      restrictedPaymentMints: false,
+      vestingCliff: new BN(0),
+      vestingDuration: new BN(0),
+      instantCashback: false,
+      referralReward: 0,
+      referralDuration: new BN(0),
     };
     const initMarketplaceAccounts = {
       signer: marketplaceAuth.publicKey,
       marketplace: marketplacePubkey,
-      rewardMint: rewardMint,
       discountMint: discountMint,
     };
     await program.methods
@@ -295,10 +564,6 @@ describe("marketplace_manager", () => {
     assert.equal(marketplaceAccount.feesConfig.feePayer.toString(), FeePayer.Seller.toString());
     assert.equal(marketplaceAccount.feesConfig.fee, fee);
     assert.equal(marketplaceAccount.feesConfig.feeReduction, feeReduction);
-    assert.equal(marketplaceAccount.rewardsConfig.rewardMint.toString(), rewardMint.toString());
-    assert.equal(marketplaceAccount.rewardsConfig.sellerReward, sellerRewardMarketplace);
-    assert.equal(marketplaceAccount.rewardsConfig.buyerReward, buyerRewardMarketplace);
-    assert.equal(marketplaceAccount.rewardsConfig.rewardsEnabled, rewardsEnabled);
   });
 
   it("Should create a product account", async () => {
@@ -324,7 +589,12 @@ describe("marketplace_manager", () => {
     const initProductParams = {
       id: [...id],
       productPrice: productPrice,
+      royalty: 0,
+      creators: [],
+      extensions: [],
       productMintBump: mintBump,
+      proof: [],
+      buyerGate: null,
     };
     const initProductAccounts = {
       systemProgram: SystemProgram.programId,
@@ -333,10 +603,17 @@ describe("marketplace_manager", () => {
       signer: seller.publicKey,
       marketplace: marketplacePubkey,
       product: productPubkey,
+      sellerAccess: getSellerAccess(seller.publicKey),
+      blockedSeller: getBlockedWallet(seller.publicKey),
+      guardian: guardianPubkey,
+      paymentMintConfig: getPaymentMintConfig(paymentMints[0]),
       productMint: productMint,
       paymentMint: paymentMints[0],
       accessMint: null,
       accessVault: null,
+      gateMint: null,
+      gateMetadata: null,
+      gateClaim: null,
     };
 
     await program.methods
@@ -363,10 +640,11 @@ describe("marketplace_manager", () => {
       signer: seller.publicKey,
       product: productPubkey,
       paymentMint: newPaymentMintPubkey,
+      paymentMintConfig: getPaymentMintConfig(newPaymentMintPubkey),
       marketplace: marketplacePubkey
     };
     await program.methods
-      .editProduct(newPrice)
+      .editProduct(newPrice, null)
       .accounts(editProductInfoAccounts)
       .signers([seller])
       .rpc()
@@ -380,11 +658,12 @@ describe("marketplace_manager", () => {
     // another wallet tries to change product data
     try {
       await program.methods
-        .editProduct(productPrice)
+        .editProduct(productPrice, null)
         .accounts({
           signer: exploiter.publicKey,
           product: productPubkey,
           paymentMint: newPaymentMintPubkey,
+          paymentMintConfig: getPaymentMintConfig(newPaymentMintPubkey),
           marketplace: marketplacePubkey
         })
         .signers([exploiter])
@@ -396,11 +675,12 @@ describe("marketplace_manager", () => {
 
     // to be able to re-use this account and its data, the account data will be the same that was before this unit test
     await program.methods
-      .editProduct(productPrice)
+      .editProduct(productPrice, null)
       .accounts({
         signer: seller.publicKey,
         product: productPubkey,
         paymentMint: paymentMints[0],
+        paymentMintConfig: getPaymentMintConfig(paymentMints[0]),
         marketplace: marketplacePubkey
       })
       .signers([seller])
@@ -464,25 +744,51 @@ describe("marketplace_manager", () => {
       marketplaceAuth: null,
       marketplace: marketplacePubkey,
       product: productPubkey,
+      sellerAccess: getSellerAccess(seller.publicKey),
+      blockedBuyer: getBlockedWallet(buyer.publicKey),
+      blockedSeller: getBlockedWallet(seller.publicKey),
+      guardian: guardianPubkey,
+      paymentMintConfig: getPaymentMintConfig(paymentMints[0]),
+      buyerGateVault: null,
+      buyerGateMint: null,
+      buyerGateMetadata: null,
       paymentMint: paymentMints[0],
       buyerTransferVault: buyerVaults[0][0],
       sellerTransferVault: sellerVaults[0][0],
       marketplaceTransferVault: marketplaceVaults[0][0],
+      campaign: null,
+      payoutMint: null,
+      payoutTokenProgram: null,
       bountyVault: null,
       sellerReward: null,
       sellerRewardVault: null,
+      sellerRewardLedger: null,
       buyerReward: null,
       buyerRewardVault: null,
+      buyerRewardLedger: null,
+      buyerCashbackVault: null,
+      referral: getReferral(buyer.publicKey),
+      referrerReward: null,
+      referrerRewardVault: null,
+      referrerRewardLedger: null,
+      sellerPointsVault: null,
+      buyerPointsVault: null,
+      referrerPointsVault: null,
+      pointsDiscount: null,
+      promotion: null,
+      promotionVault: null,
+      buyerPromotionVault: null,
+      tokenProgram2022: null,
     };
 
     const sig = await program.methods
-      .registerBuy(1)
+      .registerBuy(1, [], 0)
       .accounts(registerBuyAccounts)
       .signers([buyer])
       .postInstructions(
         [
           await program.methods
-            .registerBuy(1)
+            .registerBuy(1, [], 0)
             .accounts(registerBuyAccounts)
             .instruction()
         ]
@@ -523,17 +829,21 @@ describe("marketplace_manager", () => {
     const editMarketplaceInfoParams = {
       fee: fee,
       feeReduction: feeReduction,
-      sellerReward: sellerRewardMarketplace,
-      buyerReward: buyerRewardMarketplace,
       transferable: transferable,
       permissionless: permissionless,
-      rewardsEnabled: rewardsEnabled,
+      requestExpiry: new BN(0),
+      accessGate: { accessMint: {} },
       feePayer: FeePayer.Seller,
+      restrictedPaymentMints: false,
+      vestingCliff: new BN(0),
+      vestingDuration: new BN(0),
+      instantCashback: false,
+      referralReward: 0,
+      referralDuration: new BN(0),
     };
     const editMarketplaceInfoAccounts = {
       signer: marketplaceAuth.publicKey,
       marketplace: marketplacePubkey,
-      rewardMint: rewardMint,
       discountMint: discountMint,
     };
 
@@ -562,19 +872,45 @@ describe("marketplace_manager", () => {
       marketplaceAuth: null,
       marketplace: marketplacePubkey,
       product: productPubkey,
+      sellerAccess: getSellerAccess(seller.publicKey),
+      blockedBuyer: getBlockedWallet(buyer.publicKey),
+      blockedSeller: getBlockedWallet(seller.publicKey),
+      guardian: guardianPubkey,
+      paymentMintConfig: getPaymentMintConfig(paymentMints[0]),
+      buyerGateVault: null,
+      buyerGateMint: null,
+      buyerGateMetadata: null,
       paymentMint: paymentMints[0],
       buyerTransferVault: buyerVaults[0][0],
       sellerTransferVault: sellerVaults[0][0],
       marketplaceTransferVault: marketplaceVaults[0][0],
+      campaign: null,
+      payoutMint: null,
+      payoutTokenProgram: null,
       bountyVault: null,
       sellerReward: null,
       sellerRewardVault: null,
+      sellerRewardLedger: null,
       buyerReward: null,
       buyerRewardVault: null,
+      buyerRewardLedger: null,
+      buyerCashbackVault: null,
+      referral: getReferral(buyer.publicKey),
+      referrerReward: null,
+      referrerRewardVault: null,
+      referrerRewardLedger: null,
+      sellerPointsVault: null,
+      buyerPointsVault: null,
+      referrerPointsVault: null,
+      pointsDiscount: null,
+      promotion: null,
+      promotionVault: null,
+      buyerPromotionVault: null,
+      tokenProgram2022: null,
     };
 
     await program.methods
-      .registerBuy(1)
+      .registerBuy(1, [], 0)
       .accounts(registerBuyAccounts)
       .signers([buyer])
       .rpc(confirmOptions)
@@ -629,10 +965,11 @@ describe("marketplace_manager", () => {
       signer: seller.publicKey,
       product: productPubkey,
       paymentMint: newPaymentMintPubkey,
+      paymentMintConfig: getPaymentMintConfig(newPaymentMintPubkey),
       marketplace: marketplacePubkey
     };
     await program.methods
-      .editProduct(newPrice)
+      .editProduct(newPrice, null)
       .accounts(editProductInfoAccounts)
       .signers([seller])
       .rpc()
@@ -660,20 +997,46 @@ describe("marketplace_manager", () => {
       marketplaceAuth: marketplaceAuth.publicKey,
       marketplace: marketplacePubkey,
       product: productPubkey,
+      sellerAccess: getSellerAccess(seller.publicKey),
+      blockedBuyer: getBlockedWallet(buyer.publicKey),
+      blockedSeller: getBlockedWallet(seller.publicKey),
+      guardian: guardianPubkey,
+      paymentMintConfig: getPaymentMintConfig(newPaymentMintPubkey),
+      buyerGateVault: null,
+      buyerGateMint: null,
+      buyerGateMetadata: null,
       paymentMint: newPaymentMintPubkey,
       buyerTokenVault: null,
       buyerTransferVault: null,
       sellerTransferVault: null,
       marketplaceTransferVault: null,
+      campaign: null,
+      payoutMint: null,
+      payoutTokenProgram: null,
       bountyVault: null,
       sellerReward: null,
       sellerRewardVault: null,
+      sellerRewardLedger: null,
       buyerReward: null,
       buyerRewardVault: null,
+      buyerRewardLedger: null,
+      buyerCashbackVault: null,
+      referral: getReferral(buyer.publicKey),
+      referrerReward: null,
+      referrerRewardVault: null,
+      referrerRewardLedger: null,
+      sellerPointsVault: null,
+      buyerPointsVault: null,
+      referrerPointsVault: null,
+      pointsDiscount: null,
+      promotion: null,
+      promotionVault: null,
+      buyerPromotionVault: null,
+      tokenProgram2022: null,
     };
 
     await program.methods
-      .registerBuy(1)
+      .registerBuy(1, [], 0)
       .accounts(registerBuyAccounts)
       .signers([buyer])
       .rpc(confirmOptions)
@@ -684,10 +1047,11 @@ describe("marketplace_manager", () => {
       signer: seller.publicKey,
       product: productPubkey,
       paymentMint: paymentMints[0],
+      paymentMintConfig: getPaymentMintConfig(paymentMints[0]),
       marketplace: marketplacePubkey
     };
     await program.methods
-      .editProduct(productPrice)
+      .editProduct(productPrice, null)
       .accounts(initialEditProductInfoAccounts)
       .signers([seller])
       .rpc()
@@ -709,17 +1073,21 @@ describe("marketplace_manager", () => {
     const editMarketplaceInfoParams = {
       fee: fee,
       feeReduction: feeReduction,
-      sellerReward: sellerRewardMarketplace,
-      buyerReward: buyerRewardMarketplace,
       transferable: transferable,
       permissionless: permissionless,
-      rewardsEnabled: rewardsEnabled,
+      requestExpiry: new BN(0),
+      accessGate: { accessMint: {} },
       feePayer: FeePayer.Seller,
+      restrictedPaymentMints: false,
+      vestingCliff: new BN(0),
+      vestingDuration: new BN(0),
+      instantCashback: false,
+      referralReward: 0,
+      referralDuration: new BN(0),
     };
     const editMarketplaceInfoAccounts = {
       signer: marketplaceAuth.publicKey,
       marketplace: marketplacePubkey,
-      rewardMint: rewardMint,
       discountMint: discountMint,
     };
 
@@ -748,19 +1116,45 @@ describe("marketplace_manager", () => {
       marketplaceAuth: null,
       marketplace: marketplacePubkey,
       product: productPubkey,
+      sellerAccess: getSellerAccess(seller.publicKey),
+      blockedBuyer: getBlockedWallet(buyer.publicKey),
+      blockedSeller: getBlockedWallet(seller.publicKey),
+      guardian: guardianPubkey,
+      paymentMintConfig: getPaymentMintConfig(paymentMints[0]),
+      buyerGateVault: null,
+      buyerGateMint: null,
+      buyerGateMetadata: null,
       paymentMint: paymentMints[0],
       buyerTransferVault: buyerVaults[0][0],
       sellerTransferVault: sellerVaults[0][0],
       marketplaceTransferVault: marketplaceVaults[0][0],
+      campaign: null,
+      payoutMint: null,
+      payoutTokenProgram: null,
       bountyVault: null,
       sellerReward: null,
       sellerRewardVault: null,
+      sellerRewardLedger: null,
       buyerReward: null,
       buyerRewardVault: null,
+      buyerRewardLedger: null,
+      buyerCashbackVault: null,
+      referral: getReferral(buyer.publicKey),
+      referrerReward: null,
+      referrerRewardVault: null,
+      referrerRewardLedger: null,
+      sellerPointsVault: null,
+      buyerPointsVault: null,
+      referrerPointsVault: null,
+      pointsDiscount: null,
+      promotion: null,
+      promotionVault: null,
+      buyerPromotionVault: null,
+      tokenProgram2022: null,
     };
 
     await program.methods
-      .registerBuy(1)
+      .registerBuy(1, [], 0)
       .accounts(registerBuyAccounts)
       .signers([buyer])
       .rpc(confirmOptions)
@@ -824,22 +1218,25 @@ describe("marketplace_manager", () => {
       [marketplaceAuth as anchor.web3.Signer]
     );
     [fee, feeReduction, sellerRewardMarketplace, buyerRewardMarketplace] = [100, 20, 20, 20];
-    rewardsEnabled = true;
     const editMarketplaceInfoParams = {
       fee: fee,
       feeReduction: feeReduction,
-      sellerReward: sellerRewardMarketplace,
-      buyerReward: buyerRewardMarketplace,
       transferable: transferable,
       permissionless: permissionless,
-      rewardsEnabled: rewardsEnabled,
+      requestExpiry: new BN(0),
+      accessGate: { accessMint: {} },
       accessMintBump: accessMintBump,
       feePayer: FeePayer.Seller,
+      restrictedPaymentMints: false,
+      vestingCliff: new BN(0),
+      vestingDuration: new BN(0),
+      instantCashback: false,
+      referralReward: 0,
+      referralDuration: new BN(0),
     };
     const editMarketplaceInfoAccounts = {
       signer: marketplaceAuth.publicKey,
       marketplace: marketplacePubkey,
-      rewardMint: rewardMint,
       discountMint: discountMint,
     };
 
@@ -850,6 +1247,8 @@ describe("marketplace_manager", () => {
       .rpc()
       .catch(console.error);
 
+    [campaign, campaignEnd] = await initCampaign(rewardMint, 20);
+
     [sellerReward] = anchor.web3.PublicKey.findProgramAddressSync(
       [
         Buffer.from("reward", "utf-8"), 
@@ -948,19 +1347,45 @@ describe("marketplace_manager", () => {
       marketplaceAuth: null,
       marketplace: marketplacePubkey,
       product: productPubkey,
+      sellerAccess: getSellerAccess(seller.publicKey),
+      blockedBuyer: getBlockedWallet(buyer.publicKey),
+      blockedSeller: getBlockedWallet(seller.publicKey),
+      guardian: guardianPubkey,
+      paymentMintConfig: getPaymentMintConfig(paymentMints[0]),
+      buyerGateVault: null,
+      buyerGateMint: null,
+      buyerGateMetadata: null,
       paymentMint: paymentMints[0],
       buyerTransferVault: buyerVaults[0][0],
       sellerTransferVault: sellerVaults[0][0],
       marketplaceTransferVault: marketplaceVaults[0][0],
+      campaign: campaign,
+      payoutMint: null,
+      payoutTokenProgram: null,
       bountyVault: bountyVaults[0][0],
       sellerReward: sellerReward,
       sellerRewardVault: sellerRewardVault,
+      sellerRewardLedger: null,
       buyerReward: buyerReward,
       buyerRewardVault: buyerRewardVault,
+      buyerRewardLedger: null,
+      buyerCashbackVault: null,
+      referral: getReferral(buyer.publicKey),
+      referrerReward: null,
+      referrerRewardVault: null,
+      referrerRewardLedger: null,
+      sellerPointsVault: null,
+      buyerPointsVault: null,
+      referrerPointsVault: null,
+      pointsDiscount: null,
+      promotion: null,
+      promotionVault: null,
+      buyerPromotionVault: null,
+      tokenProgram2022: null,
     };
 
     await program.methods
-      .registerBuy(1)
+      .registerBuy(1, [], 0)
       .accounts(registerRewardBuyAccounts)
       .signers([buyer])
       .rpc(confirmOptions)
@@ -987,6 +1412,7 @@ describe("marketplace_manager", () => {
           rewardMint: rewardMint,
           receiverVault: buyerVaults[0][0],
           rewardVault: buyerRewardVaults[0][0],
+          rewardLedger: null,
         })
       .signers([buyer])
       .rpc(confirmOptions);
@@ -995,32 +1421,8 @@ describe("marketplace_manager", () => {
         assert.equal(e.error.errorCode.code, "OpenPromotion");
     }
 
-    // promo is finished with rewardsEnable = false
-    rewardsEnabled = false;
-    const changeMarketplaceInfoParams = {
-      fee: fee,
-      feeReduction: feeReduction,
-      sellerReward: sellerRewardMarketplace,
-      buyerReward: buyerRewardMarketplace,
-      transferable: transferable,
-      permissionless: permissionless,
-      rewardsEnabled: rewardsEnabled,
-      accessMintBump: accessMintBump,
-      feePayer: FeePayer.Seller,
-    };
-    const changeMarketplaceInfoAccounts = {
-      signer: marketplaceAuth.publicKey,
-      marketplace: marketplacePubkey,
-      rewardMint: rewardMint,
-      discountMint: discountMint,
-    };
-
-    await program.methods
-      .editMarketplace(changeMarketplaceInfoParams)
-      .accounts(changeMarketplaceInfoAccounts)
-      .signers([marketplaceAuth])
-      .rpc()
-      .catch(console.error);
+    // campaign is finished, rewards can be withdrawn
+    await waitCampaignEnd(campaignEnd);
 
     // only the reward auth can withdraw
     try {
@@ -1034,6 +1436,7 @@ describe("marketplace_manager", () => {
           rewardMint: rewardMint,
           receiverVault: sellerVaults[0][0],
           rewardVault: buyerRewardVaults[0][0],
+          rewardLedger: null,
         })
         .signers([seller])
         .rpc();
@@ -1052,6 +1455,7 @@ describe("marketplace_manager", () => {
         rewardMint: rewardMint,
         receiverVault: buyerVaults[0][0],
         rewardVault: buyerRewardVaults[0][0],
+        rewardLedger: null,
       })
       .signers([buyer])
       .rpc(confirmOptions)
@@ -1067,6 +1471,7 @@ describe("marketplace_manager", () => {
         rewardMint: rewardMint,
         receiverVault: sellerVaults[0][0],
         rewardVault: sellerRewardVaults[0][0],
+        rewardLedger: null,
       })
       .signers([seller])
       .rpc(confirmOptions)
@@ -1117,7 +1522,6 @@ describe("marketplace_manager", () => {
 
   it("Should register a buy with SOL as payment, with rewards active (should not give rewards and not errors)", async () => {
     [fee, feeReduction, sellerRewardMarketplace, buyerRewardMarketplace] = [100, 20, 20, 20];
-    rewardsEnabled = true;
     [rewardMint] = anchor.web3.PublicKey.findProgramAddressSync(
       [Buffer.from("null", "utf-8")],
       program.programId
@@ -1125,18 +1529,22 @@ describe("marketplace_manager", () => {
     const editMarketplaceInfoParams = {
       fee: fee,
       feeReduction: feeReduction,
-      sellerReward: sellerRewardMarketplace,
-      buyerReward: buyerRewardMarketplace,
       transferable: transferable,
       permissionless: permissionless,
-      rewardsEnabled: rewardsEnabled,
+      requestExpiry: new BN(0),
+      accessGate: { accessMint: {} },
       accessMintBump: accessMintBump,
       feePayer: FeePayer.Seller,
+      restrictedPaymentMints: false,
+      vestingCliff: new BN(0),
+      vestingDuration: new BN(0),
+      instantCashback: false,
+      referralReward: 0,
+      referralDuration: new BN(0),
     };
     const editMarketplaceInfoAccounts = {
       signer: marketplaceAuth.publicKey,
       marketplace: marketplacePubkey,
-      rewardMint: rewardMint,
       discountMint: discountMint,
     };
 
@@ -1147,13 +1555,16 @@ describe("marketplace_manager", () => {
       .rpc()
       .catch(console.error);
 
+    [campaign, campaignEnd] = await initCampaign(rewardMint, 20);
+
     const productPrice = new BN(1000);
     await program.methods
-      .editProduct(productPrice)
+      .editProduct(productPrice, null)
       .accounts({
         signer: seller.publicKey,
         product: productPubkey,
         paymentMint: NATIVE_MINT,
+        paymentMintConfig: getPaymentMintConfig(NATIVE_MINT),
         marketplace: marketplacePubkey
       })
       .signers([seller])
@@ -1177,22 +1588,48 @@ describe("marketplace_manager", () => {
       marketplaceAuth: marketplaceAuth.publicKey,
       marketplace: marketplacePubkey,
       product: productPubkey,
+      sellerAccess: getSellerAccess(seller.publicKey),
+      blockedBuyer: getBlockedWallet(buyer.publicKey),
+      blockedSeller: getBlockedWallet(seller.publicKey),
+      guardian: guardianPubkey,
+      paymentMintConfig: getPaymentMintConfig(NATIVE_MINT),
+      buyerGateVault: null,
+      buyerGateMint: null,
+      buyerGateMetadata: null,
       paymentMint: NATIVE_MINT,
       buyerTransferVault: null,
       sellerTransferVault: null,
       marketplaceTransferVault: null,
+      campaign: campaign,
+      payoutMint: null,
+      payoutTokenProgram: null,
       bountyVault: null,
       sellerReward: sellerReward,
       sellerRewardVault: null,
+      sellerRewardLedger: null,
       buyerReward: buyerReward,
       buyerRewardVault: null,
+      buyerRewardLedger: null,
+      buyerCashbackVault: null,
+      referral: getReferral(buyer.publicKey),
+      referrerReward: null,
+      referrerRewardVault: null,
+      referrerRewardLedger: null,
+      sellerPointsVault: null,
+      buyerPointsVault: null,
+      referrerPointsVault: null,
+      pointsDiscount: null,
+      promotion: null,
+      promotionVault: null,
+      buyerPromotionVault: null,
+      tokenProgram2022: null,
     };
 
     const preSellerBalance = await provider.connection.getBalance(seller.publicKey, confirmOptions);
     const preBuyerBalance = await provider.connection.getBalance(buyer.publicKey, confirmOptions);
 
     await program.methods
-      .registerBuy(1)
+      .registerBuy(1, [], 0)
       .accounts(registerRewardBuyAccounts)
       .signers([buyer])
       .rpc(confirmOptions)
@@ -1238,22 +1675,25 @@ describe("marketplace_manager", () => {
     ]);
 
     [fee, feeReduction, sellerRewardMarketplace, buyerRewardMarketplace] = [100, 20, 20, 20];
-    rewardsEnabled = true;
     const editMarketplaceInfoParams = {
       fee: fee,
       feeReduction: feeReduction,
-      sellerReward: sellerRewardMarketplace,
-      buyerReward: buyerRewardMarketplace,
       transferable: transferable,
       permissionless: permissionless,
-      rewardsEnabled: rewardsEnabled,
+      requestExpiry: new BN(0),
+      accessGate: { accessMint: {} },
       accessMintBump: accessMintBump,
       feePayer: FeePayer.Seller,
+      restrictedPaymentMints: false,
+      vestingCliff: new BN(0),
+      vestingDuration: new BN(0),
+      instantCashback: false,
+      referralReward: 0,
+      referralDuration: new BN(0),
     };
     const editMarketplaceInfoAccounts = {
       signer: marketplaceAuth.publicKey,
       marketplace: marketplacePubkey,
-      rewardMint: rewardMint,
       discountMint: discountMint,
     };
     await program.methods
@@ -1263,13 +1703,16 @@ describe("marketplace_manager", () => {
       .rpc()
       .catch(console.error);
 
+    [campaign, campaignEnd] = await initCampaign(rewardMint, 60);
+
     const productPrice = new BN(5000);
     await program.methods
-      .editProduct(productPrice)
+      .editProduct(productPrice, null)
       .accounts({
         signer: seller.publicKey,
         product: productPubkey,
         paymentMint:rewardMint,
+        paymentMintConfig: getPaymentMintConfig(rewardMint),
         marketplace: marketplacePubkey
       })
       .signers([seller])
@@ -1390,19 +1833,45 @@ describe("marketplace_manager", () => {
       marketplaceAuth: null,
       marketplace: marketplacePubkey,
       product: productPubkey,
+      sellerAccess: getSellerAccess(seller.publicKey),
+      blockedBuyer: getBlockedWallet(buyer.publicKey),
+      blockedSeller: getBlockedWallet(seller.publicKey),
+      guardian: guardianPubkey,
+      paymentMintConfig: getPaymentMintConfig(rewardMint),
+      buyerGateVault: null,
+      buyerGateMint: null,
+      buyerGateMetadata: null,
       paymentMint: rewardMint,
       buyerTransferVault: buyerVaults[1][0],
       sellerTransferVault: sellerVaults[1][0],
       marketplaceTransferVault: marketplaceVaults[1][0],
+      campaign: campaign,
+      payoutMint: null,
+      payoutTokenProgram: null,
       bountyVault: bountyVault,
       sellerReward: sellerReward,
       sellerRewardVault: sellerRewardVaults[1][0],
+      sellerRewardLedger: null,
       buyerReward: buyerReward,
       buyerRewardVault: buyerRewardVaults[1][0],
+      buyerRewardLedger: null,
+      buyerCashbackVault: null,
+      referral: getReferral(buyer.publicKey),
+      referrerReward: null,
+      referrerRewardVault: null,
+      referrerRewardLedger: null,
+      sellerPointsVault: null,
+      buyerPointsVault: null,
+      referrerPointsVault: null,
+      pointsDiscount: null,
+      promotion: null,
+      promotionVault: null,
+      buyerPromotionVault: null,
+      tokenProgram2022: null,
     };
 
     await program.methods
-      .registerBuy(1)
+      .registerBuy(1, [], 0)
       .accounts(registerRewardBuyAccounts)
       .signers([buyer])
       .rpc(confirmOptions)
@@ -1451,22 +1920,25 @@ describe("marketplace_manager", () => {
     ]);
 
     [fee, feeReduction, sellerRewardMarketplace, buyerRewardMarketplace] = [100, 20, 20, 20];
-    rewardsEnabled = true;
     const newEditMarketplaceInfoParams = {
       fee: fee,
       feeReduction: feeReduction,
-      sellerReward: sellerRewardMarketplace,
-      buyerReward: buyerRewardMarketplace,
       transferable: transferable,
       permissionless: permissionless,
-      rewardsEnabled: rewardsEnabled,
+      requestExpiry: new BN(0),
+      accessGate: { accessMint: {} },
       accessMintBump: accessMintBump,
       feePayer: FeePayer.Seller,
+      restrictedPaymentMints: false,
+      vestingCliff: new BN(0),
+      vestingDuration: new BN(0),
+      instantCashback: false,
+      referralReward: 0,
+      referralDuration: new BN(0),
     };
     const newEditMarketplaceInfoAccounts = {
       signer: marketplaceAuth.publicKey,
       marketplace: marketplacePubkey,
-      rewardMint: newRewardMint,
       discountMint: discountMint,
     };
     await program.methods
@@ -1476,6 +1948,8 @@ describe("marketplace_manager", () => {
       .rpc()
       .catch(console.error);
 
+    const [newCampaign, newCampaignEnd] = await initCampaign(newRewardMint, 60);
+
     await delay(2000);
     const [newBountyVault] = anchor.web3.PublicKey.findProgramAddressSync(
       [
@@ -1580,19 +2054,45 @@ describe("marketplace_manager", () => {
       marketplaceAuth: null,
       marketplace: marketplacePubkey,
       product: productPubkey,
+      sellerAccess: getSellerAccess(seller.publicKey),
+      blockedBuyer: getBlockedWallet(buyer.publicKey),
+      blockedSeller: getBlockedWallet(seller.publicKey),
+      guardian: guardianPubkey,
+      paymentMintConfig: getPaymentMintConfig(rewardMint),
+      buyerGateVault: null,
+      buyerGateMint: null,
+      buyerGateMetadata: null,
       paymentMint: rewardMint,
       buyerTransferVault: buyerVaults[1][0],
       sellerTransferVault: sellerVaults[1][0],
       marketplaceTransferVault: marketplaceVaults[1][0],
+      campaign: newCampaign,
+      payoutMint: null,
+      payoutTokenProgram: null,
       bountyVault: bountyVault,
       sellerReward: sellerReward,
       sellerRewardVault: sellerRewardVaults[1][0],
+      sellerRewardLedger: null,
       buyerReward: buyerReward,
       buyerRewardVault: buyerRewardVaults[1][0],
+      buyerRewardLedger: null,
+      buyerCashbackVault: null,
+      referral: getReferral(buyer.publicKey),
+      referrerReward: null,
+      referrerRewardVault: null,
+      referrerRewardLedger: null,
+      sellerPointsVault: null,
+      buyerPointsVault: null,
+      referrerPointsVault: null,
+      pointsDiscount: null,
+      promotion: null,
+      promotionVault: null,
+      buyerPromotionVault: null,
+      tokenProgram2022: null,
     };
 
     await program.methods
-      .registerBuy(1)
+      .registerBuy(1, [], 0)
       .accounts(registerNoRewardBuyAccounts)
       .signers([buyer])
       .rpc(confirmOptions)
@@ -1607,11 +2107,12 @@ describe("marketplace_manager", () => {
 
     // now change the product mint to be able to give rewards with that new mint
     await program.methods
-      .editProduct(productPrice)
+      .editProduct(productPrice, null)
       .accounts({
         signer: seller.publicKey,
         product: productPubkey,
         paymentMint: newRewardMint,
+        paymentMintConfig: getPaymentMintConfig(newRewardMint),
         marketplace: marketplacePubkey
       })
       .signers([seller])
@@ -1627,19 +2128,45 @@ describe("marketplace_manager", () => {
       marketplaceAuth: null,
       marketplace: marketplacePubkey,
       product: productPubkey,
+      sellerAccess: getSellerAccess(seller.publicKey),
+      blockedBuyer: getBlockedWallet(buyer.publicKey),
+      blockedSeller: getBlockedWallet(seller.publicKey),
+      guardian: guardianPubkey,
+      paymentMintConfig: getPaymentMintConfig(newRewardMint),
+      buyerGateVault: null,
+      buyerGateMint: null,
+      buyerGateMetadata: null,
       paymentMint: newRewardMint,
       buyerTransferVault: buyerVaults[2][0],
       sellerTransferVault: sellerVaults[2][0],
       marketplaceTransferVault: marketplaceVaults[2][0],
+      campaign: newCampaign,
+      payoutMint: null,
+      payoutTokenProgram: null,
       bountyVault: newBountyVault,
       sellerReward: sellerReward,
       sellerRewardVault: sellerRewardVaults[2][0],
+      sellerRewardLedger: null,
       buyerReward: buyerReward,
       buyerRewardVault: buyerRewardVaults[2][0],
+      buyerRewardLedger: null,
+      buyerCashbackVault: null,
+      referral: getReferral(buyer.publicKey),
+      referrerReward: null,
+      referrerRewardVault: null,
+      referrerRewardLedger: null,
+      sellerPointsVault: null,
+      buyerPointsVault: null,
+      referrerPointsVault: null,
+      pointsDiscount: null,
+      promotion: null,
+      promotionVault: null,
+      buyerPromotionVault: null,
+      tokenProgram2022: null,
     };
 
     await program.methods
-      .registerBuy(1)
+      .registerBuy(1, [], 0)
       .accounts(newRegisterRewardBuyAccounts)
       .signers([buyer])
       .rpc(confirmOptions)
@@ -1654,32 +2181,8 @@ describe("marketplace_manager", () => {
     const newBuyerRewardFunds = await getAccount(provider.connection, buyerRewardVaults[2][0]);
     assert.equal(Number(newBuyerRewardFunds.amount), newExpectedBuyerReward);
     // withdraw rewards (both mints done before)
-    // promo is finished with rewardsEnable = false
-    rewardsEnabled = false;
-    const changeMarketplaceInfoParams = {
-      fee: fee,
-      feeReduction: feeReduction,
-      sellerReward: sellerRewardMarketplace,
-      buyerReward: buyerRewardMarketplace,
-      transferable: transferable,
-      permissionless: permissionless,
-      rewardsEnabled: rewardsEnabled,
-      accessMintBump: accessMintBump,
-      feePayer: FeePayer.Seller,
-    };
-    const changeMarketplaceInfoAccounts = {
-      signer: marketplaceAuth.publicKey,
-      marketplace: marketplacePubkey,
-      rewardMint: rewardMint,
-      discountMint: discountMint,
-    };
-
-    await program.methods
-      .editMarketplace(changeMarketplaceInfoParams)
-      .accounts(changeMarketplaceInfoAccounts)
-      .signers([marketplaceAuth])
-      .rpc()
-      .catch(console.error);
+    // campaign is finished, rewards can be withdrawn
+    await waitCampaignEnd(newCampaignEnd);
 
     await delay(2000);
 
@@ -1694,6 +2197,7 @@ describe("marketplace_manager", () => {
         rewardMint: rewardMint,
         receiverVault: buyerVaults[1][0],
         rewardVault: buyerRewardVaults[1][0],
+        rewardLedger: null,
       })
       .signers([buyer])
       .rpc()
@@ -1714,6 +2218,7 @@ describe("marketplace_manager", () => {
         rewardMint: rewardMint,
         receiverVault: sellerVaults[1][0],
         rewardVault: sellerRewardVaults[1][0],
+        rewardLedger: null,
       })
       .signers([seller])
       .rpc()
@@ -1734,6 +2239,7 @@ describe("marketplace_manager", () => {
         rewardMint: newRewardMint,
         receiverVault: buyerVaults[2][0],
         rewardVault: buyerRewardVaults[2][0],
+        rewardLedger: null,
       })
       .signers([buyer])
       .rpc()
@@ -1754,6 +2260,7 @@ describe("marketplace_manager", () => {
         rewardMint: newRewardMint,
         receiverVault: sellerVaults[2][0],
         rewardVault: sellerRewardVaults[2][0],
+        rewardLedger: null,
       })
       .signers([seller])
       .rpc()
@@ -1772,10 +2279,11 @@ describe("marketplace_manager", () => {
       signer: seller.publicKey,
       product: productPubkey,
       paymentMint: newPaymentMintPubkey,
+      paymentMintConfig: getPaymentMintConfig(newPaymentMintPubkey),
       marketplace: marketplacePubkey
     };
     await program.methods
-      .editProduct(newPrice)
+      .editProduct(newPrice, null)
       .accounts(editProductInfoAccounts)
       .signers([seller])
       .rpc()
@@ -1784,21 +2292,23 @@ describe("marketplace_manager", () => {
     const editMarketplaceInfoParams = {
       fee: 100,
       feeReduction: 0,
-      sellerReward: 100,
-      buyerReward: 100,
       useCnfts: false,
       deliverToken: false,
       transferable: false,
       chainCounter: true,
       permissionless: true,
-      rewardsEnabled: false,
       feePayer: FeePayer.Buyer,
+      restrictedPaymentMints: false,
+      vestingCliff: new BN(0),
+      vestingDuration: new BN(0),
+      instantCashback: false,
+      referralReward: 0,
+      referralDuration: new BN(0),
     };
 
     const editMarketplaceInfoAccounts = {
       signer: marketplaceAuth.publicKey,
       marketplace: marketplacePubkey,
-      rewardMint: rewardMint,
       discountMint: discountMint,
     };
 
@@ -1831,20 +2341,46 @@ describe("marketplace_manager", () => {
       marketplaceAuth: marketplaceAuth.publicKey,
       marketplace: marketplacePubkey,
       product: productPubkey,
+      sellerAccess: getSellerAccess(seller.publicKey),
+      blockedBuyer: getBlockedWallet(buyer.publicKey),
+      blockedSeller: getBlockedWallet(seller.publicKey),
+      guardian: guardianPubkey,
+      paymentMintConfig: getPaymentMintConfig(newPaymentMintPubkey),
+      buyerGateVault: null,
+      buyerGateMint: null,
+      buyerGateMetadata: null,
       paymentMint: newPaymentMintPubkey,
       buyerTokenVault: null,
       buyerTransferVault: null,
       sellerTransferVault: null,
       marketplaceTransferVault: null,
+      campaign: null,
+      payoutMint: null,
+      payoutTokenProgram: null,
       bountyVault: null,
       sellerReward: null,
       sellerRewardVault: null,
+      sellerRewardLedger: null,
       buyerReward: null,
       buyerRewardVault: null,
+      buyerRewardLedger: null,
+      buyerCashbackVault: null,
+      referral: getReferral(buyer.publicKey),
+      referrerReward: null,
+      referrerRewardVault: null,
+      referrerRewardLedger: null,
+      sellerPointsVault: null,
+      buyerPointsVault: null,
+      referrerPointsVault: null,
+      pointsDiscount: null,
+      promotion: null,
+      promotionVault: null,
+      buyerPromotionVault: null,
+      tokenProgram2022: null,
     };
 
     await program.methods
-      .registerBuy(1)
+      .registerBuy(1, [], 0)
       .accounts(registerBuyAccounts)
       .signers([buyer])
       .rpc()
@@ -1923,6 +2459,9 @@ describe("marketplace_manager", () => {
         name: "DATASET",
         metadataUrl: "test",
         feeBasisPoints: 0,
+        creators: [],
+        proof: [],
+        buyerGate: null,
         productMintBump: mintBump,
     };
     const initProductAccounts = {
@@ -1937,10 +2476,17 @@ describe("marketplace_manager", () => {
         signer: seller.publicKey,
         marketplace: marketplacePubkey,
         product: productPubkey,
+        sellerAccess: getSellerAccess(seller.publicKey),
+        blockedSeller: getBlockedWallet(seller.publicKey),
+        guardian: guardianPubkey,
+        paymentMintConfig: getPaymentMintConfig(paymentMints[0]),
         productMint: productMint,
         accessMint: null,
         paymentMint: paymentMints[0],
         accessVault: null,
+        gateMint: null,
+        gateMetadata: null,
+        gateClaim: null,
         productMintVault: getAssociatedTokenAddressSync(productMint, productPubkey, true),
         masterEdition: masterEdition,
         metadata: metadata,
@@ -1984,16 +2530,42 @@ describe("marketplace_manager", () => {
       marketplaceAuth: null,
       marketplace: marketplacePubkey,
       product: productPubkey,
+      sellerAccess: getSellerAccess(seller.publicKey),
+      blockedBuyer: getBlockedWallet(buyer.publicKey),
+      blockedSeller: getBlockedWallet(seller.publicKey),
+      guardian: guardianPubkey,
+      paymentMintConfig: getPaymentMintConfig(paymentMints[0]),
+      buyerGateVault: null,
+      buyerGateMint: null,
+      buyerGateMetadata: null,
       paymentMint: paymentMints[0],
       productMint: productMint,
       buyerTransferVault: buyerVaults[0][0],
       sellerTransferVault: sellerVaults[0][0],
       marketplaceTransferVault: marketplaceVaults[0][0],
+      campaign: null,
+      payoutMint: null,
+      payoutTokenProgram: null,
       bountyVault: null,
       sellerReward: null,
       sellerRewardVault: null,
+      sellerRewardLedger: null,
       buyerReward: null,
       buyerRewardVault: null,
+      buyerRewardLedger: null,
+      buyerCashbackVault: null,
+      referral: getReferral(buyer.publicKey),
+      referrerReward: null,
+      referrerRewardVault: null,
+      referrerRewardLedger: null,
+      sellerPointsVault: null,
+      buyerPointsVault: null,
+      referrerPointsVault: null,
+      pointsDiscount: null,
+      promotion: null,
+      promotionVault: null,
+      buyerPromotionVault: null,
+      tokenProgram2022: null,
       metadata: metadata,
       masterEdition: masterEdition,
       treeAuthority: treeAuthority,
@@ -2004,7 +2576,10 @@ describe("marketplace_manager", () => {
       amount: 1,
       name: "DATASET",
       symbol: "BRICK",
-      uri: "TEST"
+      uri: "TEST",
+      proof: [],
+      paymentHookAccounts: 0,
+      creatorSigners: 0,
     };
 
     await program.methods
@@ -2026,10 +2601,11 @@ describe("marketplace_manager", () => {
       marketplace: marketplacePubkey,
       accessMint: accessMint,
       accessVault: getAssociatedTokenAddressSync(accessMint, exploiter.publicKey, false, TOKEN_2022_PROGRAM_ID),
+      sellerAccess: getSellerAccess(exploiter.publicKey),
     }
 
     const tx = await program.methods
-      .airdropAccess()
+      .airdropAccess(null)
       .accounts(accounts)
       .signers([marketplaceAuth])
       .rpc()
@@ -2042,19 +2618,23 @@ describe("marketplace_manager", () => {
     const editMarketplaceInfoParams = {
       fee: fee,
       feeReduction: feeReduction,
-      sellerReward: sellerRewardMarketplace,
-      buyerReward: buyerRewardMarketplace,
       transferable: transferable,
       permissionless: permissionless,
-      rewardsEnabled: rewardsEnabled,
+      requestExpiry: new BN(0),
+      accessGate: { accessMint: {} },
       accessMintBump: accessMintBump,
       feePayer: FeePayer.Buyer,
+      restrictedPaymentMints: false,
+      vestingCliff: new BN(0),
+      vestingDuration: new BN(0),
+      instantCashback: false,
+      referralReward: 0,
+      referralDuration: new BN(0),
     };
 
     const editMarketplaceInfoAccounts = {
       signer: marketplaceAuth.publicKey,
       marketplace: marketplacePubkey,
-      rewardMint: await createMint(provider, confirmOptions),
       discountMint: await createMint(provider, confirmOptions),
     };
 
@@ -2081,9 +2661,14 @@ describe("marketplace_manager", () => {
       marketplace: marketplacePubkey,
       request: request,
     };
+    const requestAccessParams = {
+      uri: "https://example.com/application.json",
+      attestation: new Array(32).fill(0),
+      category: "services",
+    };
     
     await program.methods
-      .requestAccess()
+      .requestAccess(requestAccessParams)
       .accounts(initRequestAccounts)
       .signers([seller])
       .rpc()
@@ -2100,10 +2685,11 @@ describe("marketplace_manager", () => {
       request: request,
       accessMint: accessMint,
       accessVault: receiverVault,
+      sellerAccess: getSellerAccess(seller.publicKey),
     };
 
     await program.methods
-      .acceptAccess()
+      .acceptAccess(null)
       .accounts(acceptRequestAccounts)
       .signers([marketplaceAuth])
       .rpc()
@@ -2128,7 +2714,12 @@ describe("marketplace_manager", () => {
     const initProductParams = {
       id: [...id],
       productPrice: productPrice,
+      royalty: 0,
+      creators: [],
+      extensions: [],
       productMintBump: mintBump,
+      proof: [],
+      buyerGate: null,
     };
     const accessVault = await getOrCreateAssociatedTokenAccount(
       provider.connection,
@@ -2147,10 +2738,17 @@ describe("marketplace_manager", () => {
       signer: seller.publicKey,
       marketplace: marketplacePubkey,
       product: productPubkey,
+      sellerAccess: getSellerAccess(seller.publicKey),
+      blockedSeller: getBlockedWallet(seller.publicKey),
+      guardian: guardianPubkey,
+      paymentMintConfig: getPaymentMintConfig(paymentMints[0]),
       productMint: productMint,
       paymentMint: paymentMints[0],
       accessMint: accessMint,
       accessVault: accessVault.address,
+      gateMint: null,
+      gateMetadata: null,
+      gateClaim: null,
     };
     await delay(1000)
 
@@ -2190,7 +2788,12 @@ describe("marketplace_manager", () => {
     const initErrorProductParams = {
       id: [...id],
       productPrice: productPrice,
+      royalty: 0,
+      creators: [],
+      extensions: [],
       productMintBump: mintBump,
+      proof: [],
+      buyerGate: null,
     };
     const initErrorProductAccounts = {
       systemProgram: SystemProgram.programId,
@@ -2199,10 +2802,17 @@ describe("marketplace_manager", () => {
       signer: buyer.publicKey,
       marketplace: marketplacePubkey,
       product: productPubkey,
+      sellerAccess: getSellerAccess(buyer.publicKey),
+      blockedSeller: getBlockedWallet(buyer.publicKey),
+      guardian: guardianPubkey,
+      paymentMintConfig: getPaymentMintConfig(paymentMints[0]),
       productMint: productMint,
       paymentMint: paymentMints[0],
       accessMint: accessMint,
       accessVault: buyerVault.address,
+      gateMint: null,
+      gateMetadata: null,
+      gateClaim: null,
     };
     try {
       await program.methods
@@ -2236,4 +2846,1219 @@ describe("marketplace_manager", () => {
       assert.isTrue(e.toString().includes("0x25"));
     }
   });
+
+  it("Should block sellers with an expired access until it is renewed", async () => {
+    const sellerAccess = getSellerAccess(seller.publicKey);
+    const accessVault = getAssociatedTokenAddressSync(accessMint, seller.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    const airdropAccessAccounts = {
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_2022_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      rent: SYSVAR_RENT_PUBKEY,
+      signer: marketplaceAuth.publicKey,
+      receiver: seller.publicKey,
+      marketplace: marketplacePubkey,
+      accessMint: accessMint,
+      accessVault: accessVault,
+      sellerAccess: sellerAccess,
+    };
+    const expiresAt = Math.floor(Date.now() / 1000) + 2;
+
+    // an access can not be granted with an expiry that has passed
+    try {
+      await program.methods
+        .airdropAccess(new BN(expiresAt - 3600))
+        .accounts(airdropAccessAccounts)
+        .signers([marketplaceAuth])
+        .rpc(confirmOptions);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "IncorrectAccessExpiry");
+    }
+
+    await program.methods
+      .airdropAccess(new BN(expiresAt))
+      .accounts(airdropAccessAccounts)
+      .signers([marketplaceAuth])
+      .rpc(confirmOptions);
+
+    // granting the access again without an expiry keeps the current one
+    await program.methods
+      .airdropAccess(null)
+      .accounts(airdropAccessAccounts)
+      .signers([marketplaceAuth])
+      .rpc(confirmOptions);
+
+    let sellerAccessAccount = await program.account.sellerAccess.fetch(sellerAccess);
+    assert.equal(sellerAccessAccount.expiresAt.toNumber(), expiresAt);
+
+    await delay(4000);
+    try {
+      await initProduct(seller, accessVault);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "AccessExpired");
+    }
+
+    const duration = 3600;
+    await program.methods
+      .renewAccess(new BN(duration))
+      .accounts({
+        signer: marketplaceAuth.publicKey,
+        marketplace: marketplacePubkey,
+        sellerAccess: sellerAccess,
+      })
+      .signers([marketplaceAuth])
+      .rpc(confirmOptions);
+
+    sellerAccessAccount = await program.account.sellerAccess.fetch(sellerAccess);
+    assert.isAtLeast(sellerAccessAccount.expiresAt.toNumber(), Math.floor(Date.now() / 1000) + duration - 60);
+
+    const [product] = await initProduct(seller, accessVault);
+    const productAccount = await program.account.product.fetch(product);
+    assert.equal(productAccount.authority.toString(), seller.publicKey.toString());
+  });
+
+  it("Should list, buy and cancel resales of product tokens", async () => {
+    const resaleFee = 100;
+    await program.methods
+      .editMarketplace({
+        fee: resaleFee,
+        feeReduction: feeReduction,
+        transferable: true,
+        permissionless: permissionless,
+        requestExpiry: new BN(0),
+        accessGate: { accessMint: {} },
+        accessMintBump: accessMintBump,
+        feePayer: FeePayer.Seller,
+        restrictedPaymentMints: false,
+        vestingCliff: new BN(0),
+        vestingDuration: new BN(0),
+        instantCashback: false,
+        referralReward: 0,
+        referralDuration: new BN(0),
+      })
+      .accounts({
+        signer: marketplaceAuth.publicKey,
+        marketplace: marketplacePubkey,
+        discountMint: await createMint(provider, confirmOptions),
+      })
+      .signers([marketplaceAuth])
+      .rpc(confirmOptions);
+
+    const accessVault = getAssociatedTokenAddressSync(accessMint, seller.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    const royalty = 500;
+    const [product, mint] = await initProduct(seller, accessVault, royalty);
+    const resellerTokenVault = await buyProductTokens(buyer, seller.publicKey, product, mint, 3);
+
+    const productAccount = await program.account.product.fetch(product);
+    assert.equal(productAccount.marketplace.toString(), marketplacePubkey.toString());
+
+    const getListing = (reseller: anchor.web3.PublicKey, listedProduct: anchor.web3.PublicKey) => {
+      const [listing] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("listing", "utf-8"), reseller.toBuffer(), listedProduct.toBuffer()],
+        program.programId
+      );
+      const [listingVault] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("listing_vault", "utf-8"), listing.toBuffer()],
+        program.programId
+      );
+      return [listing, listingVault];
+    };
+    const listResale = async (listedProduct: anchor.web3.PublicKey, listedMint: anchor.web3.PublicKey, tokenVault: anchor.web3.PublicKey, amount: number, unitPrice: number) => {
+      const [listing, listingVault] = getListing(buyer.publicKey, listedProduct);
+      await program.methods
+        .listResale({ amount: new BN(amount), unitPrice: new BN(unitPrice) })
+        .accounts({
+          signer: buyer.publicKey,
+          marketplace: marketplacePubkey,
+          guardian: guardianPubkey,
+          product: listedProduct,
+          paymentMintConfig: getPaymentMintConfig(paymentMints[0]),
+          productMint: listedMint,
+          listing: listing,
+          listingVault: listingVault,
+          resellerTokenVault: tokenVault,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([buyer])
+        .rpc(confirmOptions);
+    };
+
+    // the royalty and the fee paid by the reseller can not take more than the whole price
+    const [expensiveProduct, expensiveMint] = await initProduct(seller, accessVault, 10000 - resaleFee + 1);
+    const expensiveTokenVault = await buyProductTokens(buyer, seller.publicKey, expensiveProduct, expensiveMint, 1);
+    try {
+      await listResale(expensiveProduct, expensiveMint, expensiveTokenVault, 1, 1000);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "IncorrectRoyalty");
+    }
+
+    const unitPrice = 1000;
+    await listResale(product, mint, resellerTokenVault, 3, unitPrice);
+    const [listing, listingVault] = getListing(buyer.publicKey, product);
+    let listingAccount = await program.account.listing.fetch(listing);
+    assert.equal(listingAccount.amount.toNumber(), 3);
+    assert.equal(Number((await getAccount(provider.connection, listingVault, "confirmed", TOKEN_2022_PROGRAM_ID)).amount), 3);
+
+    const resaleBuyer = await createFundedWallet(provider, 10);
+    const resaleBuyerVault = await createFundedAssociatedTokenAccount(provider, paymentMints[0], 100000, resaleBuyer);
+    const resaleBuyerTokenVault = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      resaleBuyer,
+      mint,
+      resaleBuyer.publicKey,
+      false,
+      "confirmed",
+      confirmOptions,
+      TOKEN_2022_PROGRAM_ID,
+    );
+    const resellerTransferVault = getAssociatedTokenAddressSync(paymentMints[0], buyer.publicKey);
+    const sellerTransferVault = getAssociatedTokenAddressSync(paymentMints[0], seller.publicKey);
+    const marketplaceTransferVault = getAssociatedTokenAddressSync(paymentMints[0], marketplaceAuth.publicKey);
+    const buyResaleAccounts = {
+      signer: resaleBuyer.publicKey,
+      reseller: buyer.publicKey,
+      seller: null,
+      marketplaceAuth: null,
+      marketplace: marketplacePubkey,
+      guardian: guardianPubkey,
+      blockedBuyer: getBlockedWallet(resaleBuyer.publicKey),
+      blockedReseller: getBlockedWallet(buyer.publicKey),
+      product: product,
+      listing: listing,
+      listingVault: listingVault,
+      productMint: mint,
+      buyerTokenVault: resaleBuyerTokenVault.address,
+      paymentMint: paymentMints[0],
+      paymentMintConfig: getPaymentMintConfig(paymentMints[0]),
+      buyerTransferVault: resaleBuyerVault,
+      resellerTransferVault: resellerTransferVault,
+      sellerTransferVault: sellerTransferVault,
+      marketplaceTransferVault: marketplaceTransferVault,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+    };
+    const balanceOf = async (vault: anchor.web3.PublicKey) =>
+      Number((await getAccount(provider.connection, vault, "confirmed", TOKEN_PROGRAM_ID)).amount);
+    const resellerBalance = await balanceOf(resellerTransferVault);
+    const sellerBalance = await balanceOf(sellerTransferVault);
+    const marketplaceBalance = await balanceOf(marketplaceTransferVault);
+
+    try {
+      await program.methods
+        .buyResale(new BN(4), 0)
+        .accounts(buyResaleAccounts)
+        .signers([resaleBuyer])
+        .rpc(confirmOptions);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "IncorrectAmount");
+    }
+
+    await program.methods
+      .buyResale(new BN(2), 0)
+      .accounts(buyResaleAccounts)
+      .signers([resaleBuyer])
+      .rpc(confirmOptions);
+
+    // the seller is the fee payer, so the reseller pays the fee and the royalty
+    const totalAmount = 2 * unitPrice;
+    const totalFee = totalAmount * resaleFee / 10000;
+    const royaltyAmount = totalAmount * royalty / 10000;
+    assert.equal(await balanceOf(resaleBuyerVault), 100000 - totalAmount);
+    assert.equal(await balanceOf(marketplaceTransferVault), marketplaceBalance + totalFee);
+    assert.equal(await balanceOf(sellerTransferVault), sellerBalance + royaltyAmount);
+    assert.equal(await balanceOf(resellerTransferVault), resellerBalance + totalAmount - totalFee - royaltyAmount);
+    assert.equal(Number((await getAccount(provider.connection, resaleBuyerTokenVault.address, "confirmed", TOKEN_2022_PROGRAM_ID)).amount), 2);
+    listingAccount = await program.account.listing.fetch(listing);
+    assert.equal(listingAccount.amount.toNumber(), 1);
+
+    // the reseller recovers the token that was not sold
+    await program.methods
+      .cancelResale()
+      .accounts({
+        signer: buyer.publicKey,
+        product: product,
+        productMint: mint,
+        listing: listing,
+        listingVault: listingVault,
+        resellerTokenVault: resellerTokenVault,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([buyer])
+      .rpc(confirmOptions);
+
+    assert.isNull(await program.account.listing.fetchNullable(listing));
+    assert.isNull(await provider.connection.getAccountInfo(listingVault));
+    assert.equal(Number((await getAccount(provider.connection, resellerTokenVault, "confirmed", TOKEN_2022_PROGRAM_ID)).amount), 1);
+
+    // selling the last listed token closes the listing
+    await listResale(product, mint, resellerTokenVault, 1, unitPrice);
+    await program.methods
+      .buyResale(new BN(1), 0)
+      .accounts(buyResaleAccounts)
+      .signers([resaleBuyer])
+      .rpc(confirmOptions);
+
+    assert.isNull(await program.account.listing.fetchNullable(listing));
+    assert.isNull(await provider.connection.getAccountInfo(listingVault));
+    assert.equal(Number((await getAccount(provider.connection, resaleBuyerTokenVault.address, "confirmed", TOKEN_2022_PROGRAM_ID)).amount), 3);
+  });
+
+  it("Should deny and expire access requests", async () => {
+    try {
+      await editMarketplace({ requestExpiry: new BN(-1) });
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "IncorrectRequestExpiry");
+    }
+    const requestExpiry = 2;
+    await editMarketplace({ requestExpiry: new BN(requestExpiry) });
+
+    const requester = await createFundedWallet(provider, 10);
+    const [request] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("request", "utf-8"), requester.publicKey.toBuffer(), marketplacePubkey.toBuffer()],
+      program.programId
+    );
+    const [accessDenial] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("access_denial", "utf-8"), requester.publicKey.toBuffer(), marketplacePubkey.toBuffer()],
+      program.programId
+    );
+    const requestAccess = async () => {
+      await program.methods
+        .requestAccess({
+          uri: "https://example.com/application.json",
+          attestation: new Array(32).fill(0),
+          category: "services",
+        })
+        .accounts({
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+          signer: requester.publicKey,
+          marketplace: marketplacePubkey,
+          request: request,
+        })
+        .signers([requester])
+        .rpc(confirmOptions);
+    };
+
+    await requestAccess();
+    const reason = 7;
+    await program.methods
+      .denyAccess(reason)
+      .accounts({
+        signer: marketplaceAuth.publicKey,
+        receiver: requester.publicKey,
+        marketplace: marketplacePubkey,
+        request: request,
+        accessDenial: accessDenial,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([marketplaceAuth])
+      .rpc(confirmOptions);
+
+    assert.isNull(await program.account.access.fetchNullable(request));
+    const accessDenialAccount = await program.account.accessDenial.fetch(accessDenial);
+    assert.equal(accessDenialAccount.reason, reason);
+    assert.equal(accessDenialAccount.authority.toString(), requester.publicKey.toString());
+
+    // anyone can close a request once it has expired
+    await requestAccess();
+    const expireAccessAccounts = {
+      signer: buyer.publicKey,
+      receiver: requester.publicKey,
+      marketplace: marketplacePubkey,
+      request: request,
+    };
+    try {
+      await program.methods
+        .expireAccess()
+        .accounts(expireAccessAccounts)
+        .signers([buyer])
+        .rpc(confirmOptions);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "RequestNotExpired");
+    }
+    assert.isNotNull(await program.account.access.fetchNullable(request));
+
+    await delay((requestExpiry + 2) * 1000);
+    await program.methods
+      .expireAccess()
+      .accounts(expireAccessAccounts)
+      .signers([buyer])
+      .rpc(confirmOptions);
+    assert.isNull(await program.account.access.fetchNullable(request));
+
+    await editMarketplace({});
+  });
+
+  it("Should pay with a transfer fee mint without charging more than the price", async () => {
+    const marketplaceFee = 100;
+    await editMarketplace({ fee: marketplaceFee });
+
+    // Token-2022 payment mint that withholds 5% of every transfer
+    const transferFee = 500;
+    const feeMint = anchor.web3.Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    await provider.sendAndConfirm(
+      new Transaction()
+        .add(
+          SystemProgram.createAccount({
+            fromPubkey: provider.wallet.publicKey,
+            newAccountPubkey: feeMint.publicKey,
+            space: mintLen,
+            lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+            programId: TOKEN_2022_PROGRAM_ID,
+          })
+        )
+        .add(
+          createInitializeTransferFeeConfigInstruction(
+            feeMint.publicKey,
+            provider.wallet.publicKey,
+            provider.wallet.publicKey,
+            transferFee,
+            BigInt(1_000_000),
+            TOKEN_2022_PROGRAM_ID,
+          )
+        )
+        .add(
+          createInitializeMintInstruction(
+            feeMint.publicKey,
+            0,
+            provider.wallet.publicKey,
+            null,
+            TOKEN_2022_PROGRAM_ID,
+          )
+        ),
+      [feeMint],
+      confirmOptions
+    );
+
+    const feeBuyer = await createFundedWallet(provider, 10);
+    const createFeeVault = async (owner: anchor.web3.PublicKey) => (await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      feeBuyer,
+      feeMint.publicKey,
+      owner,
+      false,
+      "confirmed",
+      confirmOptions,
+      TOKEN_2022_PROGRAM_ID,
+    )).address;
+    const buyerFeeVault = await createFeeVault(feeBuyer.publicKey);
+    const sellerFeeVault = await createFeeVault(seller.publicKey);
+    const marketplaceFeeVault = await createFeeVault(marketplaceAuth.publicKey);
+    await mintTo(
+      provider.connection,
+      feeBuyer,
+      feeMint.publicKey,
+      buyerFeeVault,
+      (provider.wallet as anchor.Wallet).payer,
+      100000,
+      [],
+      confirmOptions,
+      TOKEN_2022_PROGRAM_ID,
+    );
+
+    const accessVault = getAssociatedTokenAddressSync(accessMint, seller.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    const [product, mint] = await initProduct(seller, accessVault, 0, feeMint.publicKey);
+    await buyProductTokens(feeBuyer, seller.publicKey, product, mint, 10, feeMint.publicKey, TOKEN_2022_PROGRAM_ID);
+
+    // the marketplace fee is calculated from the price net of the transfer fee and grossed up so it is received in full
+    const balanceOf = async (vault: anchor.web3.PublicKey) =>
+      Number((await getAccount(provider.connection, vault, "confirmed", TOKEN_2022_PROGRAM_ID)).amount);
+    const withheld = (amount: number) => Math.ceil(amount * transferFee / 10000);
+    const totalAmount = 10 * 100;
+    const netAmount = totalAmount - withheld(totalAmount);
+    const totalFee = Math.floor(netAmount * marketplaceFee / 10000);
+    const grossFee = Math.ceil(totalFee * 10000 / (10000 - transferFee));
+    assert.equal(await balanceOf(buyerFeeVault), 100000 - totalAmount - grossFee);
+    assert.equal(await balanceOf(sellerFeeVault), netAmount);
+    assert.equal(await balanceOf(marketplaceFeeVault), totalFee);
+
+    await editMarketplace({});
+  });
+
+  it("Should stop the sales of revoked sellers", async () => {
+    const revokedSeller = await createFundedWallet(provider, 10);
+    const accessVault = getAssociatedTokenAddressSync(accessMint, revokedSeller.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    const sellerAccess = getSellerAccess(revokedSeller.publicKey);
+    const airdropAccess = async () => {
+      await program.methods
+        .airdropAccess(null)
+        .accounts({
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          signer: marketplaceAuth.publicKey,
+          receiver: revokedSeller.publicKey,
+          marketplace: marketplacePubkey,
+          accessMint: accessMint,
+          accessVault: accessVault,
+          sellerAccess: sellerAccess,
+        })
+        .signers([marketplaceAuth])
+        .rpc(confirmOptions);
+    };
+    await airdropAccess();
+    const [product, mint] = await initProduct(revokedSeller, accessVault);
+
+    await program.methods
+      .revokeAccess({ burn: {} })
+      .accounts({
+        signer: marketplaceAuth.publicKey,
+        seller: revokedSeller.publicKey,
+        marketplace: marketplacePubkey,
+        accessMint: accessMint,
+        accessVault: accessVault,
+        sellerAccess: sellerAccess,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([marketplaceAuth])
+      .rpc(confirmOptions);
+
+    const sellerAccessAccount = await program.account.sellerAccess.fetch(sellerAccess);
+    assert.isTrue(sellerAccessAccount.revoked);
+    assert.equal(Number((await getAccount(provider.connection, accessVault, "confirmed", TOKEN_2022_PROGRAM_ID)).amount), 0);
+
+    // the products created before the revocation can not be sold
+    try {
+      await buyProductTokens(buyer, revokedSeller.publicKey, product, mint, 1);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "AccessRevoked");
+    }
+
+    // granting the access again resumes the sales
+    await airdropAccess();
+    const buyerTokenVault = await buyProductTokens(buyer, revokedSeller.publicKey, product, mint, 1);
+    assert.equal(Number((await getAccount(provider.connection, buyerTokenVault, "confirmed", TOKEN_2022_PROGRAM_ID)).amount), 1);
+  });
+
+  it("Should block sellers and buyers with the blocklist", async () => {
+    const [blocklist] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("blocklist", "utf-8"), marketplacePubkey.toBuffer()],
+      program.programId
+    );
+    const delegate = await createFundedWallet(provider, 10);
+    await program.methods
+      .initBlocklist(marketplaceAuth.publicKey)
+      .accounts({
+        signer: marketplaceAuth.publicKey,
+        marketplace: marketplacePubkey,
+        blocklist: blocklist,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([marketplaceAuth])
+      .rpc(confirmOptions);
+
+    // only the marketplace authority can change the delegate
+    const editBlocklist = async (signer: anchor.web3.Keypair, newDelegate: anchor.web3.PublicKey) => {
+      await program.methods
+        .editBlocklist(newDelegate)
+        .accounts({
+          signer: signer.publicKey,
+          marketplace: marketplacePubkey,
+          blocklist: blocklist,
+        })
+        .signers([signer])
+        .rpc(confirmOptions);
+    };
+    try {
+      await editBlocklist(delegate, delegate.publicKey);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "ConstraintSeeds");
+    }
+    await editBlocklist(marketplaceAuth, delegate.publicKey);
+    assert.equal((await program.account.blocklist.fetch(blocklist)).delegate.toString(), delegate.publicKey.toString());
+
+    const blockWallet = async (wallet: anchor.web3.PublicKey) => {
+      await program.methods
+        .blockWallet()
+        .accounts({
+          signer: delegate.publicKey,
+          wallet: wallet,
+          marketplace: marketplacePubkey,
+          blocklist: blocklist,
+          blockedWallet: getBlockedWallet(wallet),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([delegate])
+        .rpc(confirmOptions);
+    };
+    const unblockWallet = async (wallet: anchor.web3.PublicKey) => {
+      await program.methods
+        .unblockWallet()
+        .accounts({
+          signer: delegate.publicKey,
+          marketplace: marketplacePubkey,
+          blocklist: blocklist,
+          blockedWallet: getBlockedWallet(wallet),
+        })
+        .signers([delegate])
+        .rpc(confirmOptions);
+    };
+
+    const accessVault = getAssociatedTokenAddressSync(accessMint, seller.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    const [product, mint] = await initProduct(seller, accessVault);
+
+    // the products of a blocked seller can not be bought
+    await blockWallet(seller.publicKey);
+    try {
+      await buyProductTokens(buyer, seller.publicKey, product, mint, 1);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "Blocked");
+    }
+    try {
+      await initProduct(seller, accessVault);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "Blocked");
+    }
+    await unblockWallet(seller.publicKey);
+    assert.isNull(await provider.connection.getAccountInfo(getBlockedWallet(seller.publicKey)));
+
+    // blocked buyers can not buy
+    await blockWallet(buyer.publicKey);
+    try {
+      await buyProductTokens(buyer, seller.publicKey, product, mint, 1);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "Blocked");
+    }
+    await unblockWallet(buyer.publicKey);
+
+    const buyerTokenVault = await buyProductTokens(buyer, seller.publicKey, product, mint, 1);
+    assert.equal(Number((await getAccount(provider.connection, buyerTokenVault, "confirmed", TOKEN_2022_PROGRAM_ID)).amount), 1);
+  });
+
+  it("Should gate buyers with tokens and collection NFTs", async () => {
+    const accessVault = getAssociatedTokenAddressSync(accessMint, seller.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    const gateMint = await createMint(provider, confirmOptions);
+    const buyerGateVault = await createFundedAssociatedTokenAccount(provider, gateMint, 1, buyer);
+
+    // token gates need at least the minimum amount
+    const [tokenProduct, tokenMint] = await initProduct(seller, accessVault, 0, paymentMints[0], {
+      token: { mint: gateMint, minAmount: new BN(2) },
+    });
+    try {
+      await buyProductTokens(buyer, seller.publicKey, tokenProduct, tokenMint, 1, paymentMints[0], TOKEN_PROGRAM_ID, buyerGateVault, null);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "NotInWithelist");
+    }
+    await mintTo(
+      provider.connection,
+      buyer,
+      gateMint,
+      buyerGateVault,
+      (provider.wallet as anchor.Wallet).payer,
+      1,
+      [],
+      confirmOptions,
+    );
+    const buyerTokenVault = await buyProductTokens(buyer, seller.publicKey, tokenProduct, tokenMint, 1, paymentMints[0], TOKEN_PROGRAM_ID, buyerGateVault, null);
+    assert.equal(Number((await getAccount(provider.connection, buyerTokenVault, "confirmed", TOKEN_2022_PROGRAM_ID)).amount), 1);
+
+    // collection gates only accept NFTs, a fungible token can not be passed as a member
+    const [collectionProduct, collectionMint] = await initProduct(seller, accessVault, 0, paymentMints[0], {
+      collection: { collection: anchor.web3.Keypair.generate().publicKey },
+    });
+    try {
+      await buyProductTokens(buyer, seller.publicKey, collectionProduct, collectionMint, 1, paymentMints[0], TOKEN_PROGRAM_ID, buyerGateVault, gateMint);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "IncorrectGateMint");
+    }
+  });
+
+  it("Should store the creators of a product and reject invalid ones", async () => {
+    const accessVault = getAssociatedTokenAddressSync(accessMint, seller.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    const coCreator = anchor.web3.Keypair.generate().publicKey;
+
+    // without creators the seller gets the whole share
+    const [defaultProduct] = await initProduct(seller, accessVault, 500);
+    const defaultCreators = (await program.account.product.fetch(defaultProduct)).creators;
+    assert.equal(defaultCreators.length, 1);
+    assert.isTrue(defaultCreators[0].address.equals(seller.publicKey));
+    assert.equal(defaultCreators[0].share, 100);
+
+    const [product] = await initProduct(seller, accessVault, 500, paymentMints[0], null, [
+      { address: seller.publicKey, share: 70 },
+      { address: coCreator, share: 30 },
+    ]);
+    const creators = (await program.account.product.fetch(product)).creators;
+    assert.equal(creators.length, 2);
+    assert.isTrue(creators[1].address.equals(coCreator));
+    assert.equal(creators[1].share, 30);
+
+    // repeated creators and creators without share are rejected
+    try {
+      await initProduct(seller, accessVault, 500, paymentMints[0], null, [
+        { address: coCreator, share: 50 },
+        { address: coCreator, share: 50 },
+      ]);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "IncorrectCreators");
+    }
+    try {
+      await initProduct(seller, accessVault, 500, paymentMints[0], null, [
+        { address: seller.publicKey, share: 100 },
+        { address: coCreator, share: 0 },
+      ]);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "IncorrectCreators");
+    }
+  });
+
+  it("Should start promotions at their start time and close them on withdraw", async () => {
+    const accessVault = getAssociatedTokenAddressSync(accessMint, seller.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    const [product] = await initProduct(seller, accessVault);
+
+    const getPromotion = (promotionId: number[]) => {
+      const [promotion] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("promotion", "utf-8"), product.toBuffer(), Buffer.from(promotionId)],
+        program.programId
+      );
+      const [promotionVault] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("promotion_vault", "utf-8"), promotion.toBuffer()],
+        program.programId
+      );
+      return [promotion, promotionVault];
+    };
+    const initPromotion = async (promotionId: number[], start: number, end: number) => {
+      const [promotion, promotionVault] = getPromotion(promotionId);
+      await program.methods
+        .initPromotion({
+          id: promotionId,
+          start: new BN(start),
+          end: new BN(end),
+          buyerReward: 100,
+        })
+        .accounts({
+          signer: seller.publicKey,
+          product: product,
+          paymentMint: paymentMints[0],
+          promotion: promotion,
+          promotionVault: promotionVault,
+          rent: SYSVAR_RENT_PUBKEY,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([seller])
+        .rpc(confirmOptions);
+    };
+
+    // the promotion must end after it starts
+    const now = Math.floor(Date.now() / 1000);
+    try {
+      await initPromotion([...parse(uuid())], now + 10, now + 5);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "IncorrectCampaignDates");
+    }
+
+    const promotionId = [...parse(uuid())];
+    const [promotion, promotionVault] = getPromotion(promotionId);
+    const promotionEnd = now + 3;
+    await initPromotion(promotionId, now, promotionEnd);
+    const promotionAccount = await program.account.promotion.fetch(promotion);
+    assert.equal(promotionAccount.start.toNumber(), now);
+    assert.equal(promotionAccount.end.toNumber(), promotionEnd);
+
+    const promotionFunds = 1000;
+    await mintTo(
+      provider.connection,
+      seller,
+      paymentMints[0],
+      promotionVault,
+      (provider.wallet as anchor.Wallet).payer,
+      promotionFunds,
+      [],
+      confirmOptions,
+    );
+
+    // the seller recovers the funds once the promotion ends and the promotion is closed
+    await waitCampaignEnd(promotionEnd);
+    const sellerFunds = Number((await getAccount(provider.connection, sellerVaults[0][0])).amount);
+    await program.methods
+      .withdrawPromotion()
+      .accounts({
+        signer: seller.publicKey,
+        promotion: promotion,
+        promotionVault: promotionVault,
+        paymentMint: paymentMints[0],
+        receiverVault: sellerVaults[0][0],
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([seller])
+      .rpc(confirmOptions);
+    assert.equal(Number((await getAccount(provider.connection, sellerVaults[0][0])).amount), sellerFunds + promotionFunds);
+    assert.isNull(await provider.connection.getAccountInfo(promotion));
+    assert.isNull(await provider.connection.getAccountInfo(promotionVault));
+
+    // the product can run another promotion with a new id
+    const nextPromotionId = [...parse(uuid())];
+    await initPromotion(nextPromotionId, now, now + 60);
+    assert.isNotNull(await provider.connection.getAccountInfo(getPromotion(nextPromotionId)[0]));
+  });
+
+  it("Should fund the bounty vault and withdraw it once every campaign is closed", async () => {
+    const withdrawBounty = async (amount: number) => {
+      await program.methods
+        .withdrawBounty(new BN(amount))
+        .accounts({
+          signer: marketplaceAuth.publicKey,
+          marketplace: marketplacePubkey,
+          rewardMint: paymentMints[0],
+          bountyVault: bountyVaults[0][0],
+          receiverVault: marketplaceVaults[0][0],
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([marketplaceAuth])
+        .rpc(confirmOptions);
+    };
+
+    // anyone can fund the bounty vault
+    const bountyFunds = Number((await getAccount(provider.connection, bountyVaults[0][0])).amount);
+    const fundAmount = 1000;
+    await program.methods
+      .fundBounty(new BN(fundAmount))
+      .accounts({
+        signer: seller.publicKey,
+        marketplace: marketplacePubkey,
+        guardian: guardianPubkey,
+        rewardMint: paymentMints[0],
+        funderVault: sellerVaults[0][0],
+        bountyVault: bountyVaults[0][0],
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([seller])
+      .rpc(confirmOptions);
+    assert.equal(Number((await getAccount(provider.connection, bountyVaults[0][0])).amount), bountyFunds + fundAmount);
+
+    // the budget is locked while there are open campaigns, even if they have already ended
+    const campaigns = await program.account.rewardCampaign.all([
+      { memcmp: { offset: 8, bytes: marketplacePubkey.toBase58() } },
+    ]);
+    assert.isAbove(campaigns.length, 0);
+    try {
+      await withdrawBounty(fundAmount);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "OpenCampaigns");
+    }
+
+    // the marketplace authority can close campaigns before they end
+    const [openCampaign] = await initCampaign(paymentMints[0], 600);
+    for (const campaignPubkey of [openCampaign, ...campaigns.map((campaign) => campaign.publicKey)]) {
+      await program.methods
+        .closeCampaign()
+        .accounts({
+          signer: marketplaceAuth.publicKey,
+          marketplace: marketplacePubkey,
+          campaign: campaignPubkey,
+        })
+        .signers([marketplaceAuth])
+        .rpc(confirmOptions);
+      assert.isNull(await provider.connection.getAccountInfo(campaignPubkey));
+    }
+    assert.equal((await program.account.marketplace.fetch(marketplacePubkey)).rewardsConfig.openCampaigns, 0);
+
+    const marketplaceFunds = Number((await getAccount(provider.connection, marketplaceVaults[0][0])).amount);
+    await withdrawBounty(fundAmount);
+    assert.equal(Number((await getAccount(provider.connection, bountyVaults[0][0])).amount), bountyFunds);
+    assert.equal(Number((await getAccount(provider.connection, marketplaceVaults[0][0])).amount), marketplaceFunds + fundAmount);
+  });
+
+  it("Should let sellers edit the buyer gate and reject the access mint as a buyer gate", async () => {
+    const accessVault = getAssociatedTokenAddressSync(accessMint, seller.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    const gateMint = await createMint(provider, confirmOptions);
+    const buyerGateVault = await createFundedAssociatedTokenAccount(provider, gateMint, 1, buyer);
+    const editBuyerGate = async (product: anchor.web3.PublicKey, buyerGate: Record<string, any> | null) => {
+      await program.methods
+        .editProduct(new BN(100), buyerGate)
+        .accounts({
+          signer: seller.publicKey,
+          product: product,
+          paymentMint: paymentMints[0],
+          paymentMintConfig: getPaymentMintConfig(paymentMints[0]),
+          marketplace: marketplacePubkey,
+        })
+        .signers([seller])
+        .rpc(confirmOptions);
+    };
+
+    // the access token gates sellers, holding it does not make a wallet an allowed buyer
+    try {
+      await initProduct(seller, accessVault, 0, paymentMints[0], { accessMint: {} });
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "IncorrectBuyerGate");
+    }
+
+    const [product, mint] = await initProduct(seller, accessVault);
+    try {
+      await editBuyerGate(product, { accessMint: {} });
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "IncorrectBuyerGate");
+    }
+
+    // once the gate is added buyers need the gate token
+    await editBuyerGate(product, { token: { mint: gateMint, minAmount: new BN(1) } });
+    assert.isNotNull((await program.account.product.fetch(product)).buyerGate);
+    try {
+      await buyProductTokens(buyer, seller.publicKey, product, mint, 1);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "OptionalAccountNotProvided");
+    }
+    await buyProductTokens(buyer, seller.publicKey, product, mint, 1, paymentMints[0], TOKEN_PROGRAM_ID, buyerGateVault, null);
+
+    // removing the gate opens the product to every buyer
+    await editBuyerGate(product, null);
+    assert.isNull((await program.account.product.fetch(product)).buyerGate);
+    const buyerTokenVault = await buyProductTokens(buyer, seller.publicKey, product, mint, 1);
+    assert.equal(Number((await getAccount(provider.connection, buyerTokenVault, "confirmed", TOKEN_2022_PROGRAM_ID)).amount), 2);
+  });
+
+  it("Should redeem product tokens burning them with token-2022", async () => {
+    const accessVault = getAssociatedTokenAddressSync(accessMint, seller.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    const [product, mint] = await initProduct(seller, accessVault);
+    const buyerTokenVault = await buyProductTokens(buyer, seller.publicKey, product, mint, 2);
+    const redeem = async (amount: number, tokenProgram = TOKEN_2022_PROGRAM_ID): Promise<anchor.web3.PublicKey> => {
+      const redemptionId = parse(uuid());
+      const [redemption] = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("redemption", "utf-8"),
+          buyer.publicKey.toBuffer(),
+          product.toBuffer(),
+          redemptionId,
+        ],
+        program.programId
+      );
+      await program.methods
+        .redeem({
+          id: [...redemptionId],
+          amount: new BN(amount),
+          reference: Array(32).fill(7),
+        })
+        .accounts({
+          signer: buyer.publicKey,
+          product: product,
+          productMint: mint,
+          buyerTokenVault: buyerTokenVault,
+          redemption: redemption,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: tokenProgram,
+        })
+        .signers([buyer])
+        .rpc(confirmOptions);
+
+      return redemption;
+    };
+
+    // product mints are always token-2022
+    try {
+      await redeem(1, TOKEN_PROGRAM_ID);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "IncorrectTokenProgram");
+    }
+    try {
+      await redeem(0);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "IncorrectAmount");
+    }
+
+    const redemption = await redeem(1);
+    const redemptionAccount = await program.account.redemption.fetch(redemption);
+    assert.isTrue(redemptionAccount.authority.equals(buyer.publicKey));
+    assert.isTrue(redemptionAccount.product.equals(product));
+    assert.equal(Number(redemptionAccount.amount), 1);
+    assert.deepEqual(redemptionAccount.reference, Array(32).fill(7));
+    assert.equal(Number((await getAccount(provider.connection, buyerTokenVault, "confirmed", TOKEN_2022_PROGRAM_ID)).amount), 1);
+  });
+
+  it("Should block purchases while paused and only let the authorities toggle the pause", async () => {
+    const accessVault = getAssociatedTokenAddressSync(accessMint, seller.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    const [product, mint] = await initProduct(seller, accessVault);
+    const registerBuy = async () => {
+      await program.methods
+        .registerBuy(1, [], 0)
+        .accounts({
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          signer: buyer.publicKey,
+          seller: null,
+          marketplaceAuth: null,
+          marketplace: marketplacePubkey,
+          product: product,
+          sellerAccess: getSellerAccess(seller.publicKey),
+          blockedBuyer: getBlockedWallet(buyer.publicKey),
+          blockedSeller: getBlockedWallet(seller.publicKey),
+          guardian: guardianPubkey,
+          paymentMintConfig: getPaymentMintConfig(paymentMints[0]),
+          buyerGateVault: null,
+          buyerGateMint: null,
+          buyerGateMetadata: null,
+          paymentMint: paymentMints[0],
+          buyerTransferVault: buyerVaults[0][0],
+          sellerTransferVault: sellerVaults[0][0],
+          marketplaceTransferVault: marketplaceVaults[0][0],
+          campaign: null,
+          payoutMint: null,
+          payoutTokenProgram: null,
+          bountyVault: null,
+          sellerReward: null,
+          sellerRewardVault: null,
+          sellerRewardLedger: null,
+          buyerReward: null,
+          buyerRewardVault: null,
+          buyerRewardLedger: null,
+          buyerCashbackVault: null,
+          referral: getReferral(buyer.publicKey),
+          referrerReward: null,
+          referrerRewardVault: null,
+          referrerRewardLedger: null,
+          sellerPointsVault: null,
+          buyerPointsVault: null,
+          referrerPointsVault: null,
+          pointsDiscount: null,
+          promotion: null,
+          promotionVault: null,
+          buyerPromotionVault: null,
+          tokenProgram2022: null,
+        })
+        .signers([buyer])
+        .rpc(confirmOptions);
+    };
+    // both purchase instructions have to fail while any of the pauses is active
+    const assertPaused = async () => {
+      try {
+        await registerBuy();
+      } catch (e) {
+        if (e as anchor.AnchorError)
+          assert.equal(e.error.errorCode.code, "Paused");
+      }
+      try {
+        await buyProductTokens(buyer, seller.publicKey, product, mint, 1);
+      } catch (e) {
+        if (e as anchor.AnchorError)
+          assert.equal(e.error.errorCode.code, "Paused");
+      }
+    };
+    const pauseMarketplace = async (signer: anchor.web3.Keypair, marketplace: anchor.web3.PublicKey, paused: boolean) => {
+      await program.methods
+        .pauseMarketplace(paused)
+        .accounts({ signer: signer.publicKey, marketplace: marketplace })
+        .signers([signer])
+        .rpc(confirmOptions);
+    };
+    const setGuardianPause = async (signer: anchor.web3.Keypair, paused: boolean) => {
+      await program.methods
+        .setGuardianPause(paused)
+        .accounts({ signer: signer.publicKey, guardian: guardianPubkey })
+        .signers([signer])
+        .rpc(confirmOptions);
+    };
+
+    // only the marketplace authority can pause its marketplace
+    try {
+      await pauseMarketplace(seller, marketplacePubkey, true);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "ConstraintSeeds");
+    }
+    assert.isFalse((await program.account.marketplace.fetch(marketplacePubkey)).paused);
+
+    await pauseMarketplace(marketplaceAuth, marketplacePubkey, true);
+    assert.isTrue((await program.account.marketplace.fetch(marketplacePubkey)).paused);
+    await assertPaused();
+    await pauseMarketplace(marketplaceAuth, marketplacePubkey, false);
+    await registerBuy();
+
+    // the upgrade authority of the program creates the guardian and chooses who can pause every marketplace
+    const guardianAuth = await createFundedWallet(provider, 10);
+    const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+    try {
+      await program.methods
+        .initGuardian(exploiter.publicKey)
+        .accounts({
+          signer: exploiter.publicKey,
+          guardian: guardianPubkey,
+          program: program.programId,
+          programData: programData,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([exploiter])
+        .rpc(confirmOptions);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "IncorrectAuthority");
+    }
+    await program.methods
+      .initGuardian(guardianAuth.publicKey)
+      .accounts({
+        signer: provider.wallet.publicKey,
+        guardian: guardianPubkey,
+        program: program.programId,
+        programData: programData,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc(confirmOptions);
+
+    // the marketplace authority can not use the guardian
+    try {
+      await setGuardianPause(marketplaceAuth, true);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "IncorrectAuthority");
+    }
+    assert.isFalse((await program.account.guardian.fetch(guardianPubkey)).paused);
+
+    await setGuardianPause(guardianAuth, true);
+    assert.isTrue((await program.account.guardian.fetch(guardianPubkey)).paused);
+    await assertPaused();
+    await setGuardianPause(guardianAuth, false);
+    await registerBuy();
+    const buyerTokenVault = await buyProductTokens(buyer, seller.publicKey, product, mint, 1);
+    assert.equal(Number((await getAccount(provider.connection, buyerTokenVault, "confirmed", TOKEN_2022_PROGRAM_ID)).amount), 1);
+  });
+
+  it("Should pay the seller and buyer rewards of a fungible purchase", async () => {
+    const accessVault = getAssociatedTokenAddressSync(accessMint, seller.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    const [product, mint] = await initProduct(seller, accessVault);
+    await editMarketplace({});
+
+    // the campaign budget is paid from the bounty vault of the payment mint
+    await program.methods
+      .fundBounty(new BN(1000000))
+      .accounts({
+        signer: marketplaceAuth.publicKey,
+        marketplace: marketplacePubkey,
+        guardian: guardianPubkey,
+        rewardMint: paymentMints[0],
+        funderVault: marketplaceVaults[0][0],
+        bountyVault: bountyVaults[0][0],
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([marketplaceAuth])
+      .rpc(confirmOptions);
+    [sellerRewardMarketplace, buyerRewardMarketplace] = [200, 100];
+    const [fungibleCampaign] = await initCampaign(paymentMints[0], 60);
+    const campaignAccount = await program.account.rewardCampaign.fetch(fungibleCampaign);
+
+    const buyerTokenVault = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      buyer,
+      mint,
+      buyer.publicKey,
+      false,
+      "confirmed",
+      confirmOptions,
+      TOKEN_2022_PROGRAM_ID,
+    );
+    const sellerRewardFunds = Number((await getAccount(provider.connection, sellerRewardVaults[0][0])).amount);
+    const buyerRewardFunds = Number((await getAccount(provider.connection, buyerRewardVaults[0][0])).amount);
+    const amount = 1000;
+    await program.methods
+      .registerBuyFungible(amount, [], 0)
+      .accounts({
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        signer: buyer.publicKey,
+        seller: null,
+        marketplaceAuth: null,
+        marketplace: marketplacePubkey,
+        guardian: guardianPubkey,
+        product: product,
+        sellerAccess: getSellerAccess(seller.publicKey),
+        blockedBuyer: getBlockedWallet(buyer.publicKey),
+        blockedSeller: getBlockedWallet(seller.publicKey),
+        buyerGateVault: null,
+        buyerGateMint: null,
+        buyerGateMetadata: null,
+        productMint: mint,
+        paymentMint: paymentMints[0],
+        paymentMintConfig: getPaymentMintConfig(paymentMints[0]),
+        buyerTokenVault: buyerTokenVault.address,
+        buyerTransferVault: buyerVaults[0][0],
+        sellerTransferVault: sellerVaults[0][0],
+        marketplaceTransferVault: marketplaceVaults[0][0],
+        campaign: fungibleCampaign,
+        payoutMint: null,
+        payoutTokenProgram: null,
+        bountyVault: bountyVaults[0][0],
+        sellerReward: sellerReward,
+        sellerRewardVault: sellerRewardVaults[0][0],
+        sellerRewardLedger: null,
+        buyerReward: buyerReward,
+        buyerRewardVault: buyerRewardVaults[0][0],
+        buyerRewardLedger: null,
+        buyerCashbackVault: null,
+        referral: getReferral(buyer.publicKey),
+        referrerReward: null,
+        referrerRewardVault: null,
+        referrerRewardLedger: null,
+        sellerPointsVault: null,
+        buyerPointsVault: null,
+        referrerPointsVault: null,
+        pointsDiscount: null,
+        promotion: null,
+        promotionVault: null,
+        buyerPromotionVault: null,
+        tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([buyer])
+      .rpc(confirmOptions);
+
+    const totalAmount = amount * 100;
+    const expectedSellerReward = Math.floor(totalAmount * campaignAccount.sellerReward / 10000);
+    const expectedBuyerReward = Math.floor(totalAmount * campaignAccount.buyerReward / 10000);
+    assert.isAbove(expectedSellerReward, 0);
+    assert.isAbove(expectedBuyerReward, 0);
+    assert.equal(Number((await getAccount(provider.connection, sellerRewardVaults[0][0])).amount), sellerRewardFunds + expectedSellerReward);
+    assert.equal(Number((await getAccount(provider.connection, buyerRewardVaults[0][0])).amount), buyerRewardFunds + expectedBuyerReward);
+    assert.equal(Number((await program.account.rewardCampaign.fetch(fungibleCampaign)).spent), expectedSellerReward + expectedBuyerReward);
+    assert.equal(Number((await getAccount(provider.connection, buyerTokenVault.address, "confirmed", TOKEN_2022_PROGRAM_ID)).amount), amount);
+  });
+
+  it("Should only migrate accounts with the previous layout", async () => {
+    try {
+      await program.methods
+        .migrateMarketplace(new anchor.BN(0))
+        .accounts({
+          signer: marketplaceAuth.publicKey,
+          marketplace: marketplacePubkey,
+          campaign: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([marketplaceAuth])
+        .rpc(confirmOptions);
+    } catch (e) {
+      if (e as anchor.AnchorError)
+        assert.equal(e.error.errorCode.code, "IncorrectAccountVersion");
+    }
+    const marketplaceAccount = await program.account.marketplace.fetch(marketplacePubkey);
+    assert.isTrue(marketplaceAccount.authority.equals(marketplaceAuth.publicKey));
+  });
 })
\ No newline at end of file